@@ -0,0 +1,347 @@
+// cli.rs
+// Command-line flags for the Vigenere breaker.
+//
+// With no flags the breaker behaves as before: paste ciphertext, press Ctrl+D,
+// optionally answer the key-length prompt. Flags make it scriptable.
+
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+/// Vigenere breaker: Kasiski -> chi-sq column solve -> tetragram scoring
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// Other tools; without one, run the Kasiski breaker
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Read ciphertext from FILE instead of stdin ("-" also means stdin)
+    #[arg(short, long, value_name = "FILE", global = true)]
+    pub input: Option<PathBuf>,
+
+    /// Quadgram counts file ("TION 13168375" per line) replacing the built-in table
+    #[arg(short, long, value_name = "FILE", global = true)]
+    pub quadgrams: Option<PathBuf>,
+
+    /// Word list ("word" or "word count" per line, most frequent first) for word segmentation
+    #[arg(short, long, value_name = "FILE", global = true)]
+    pub words: Option<PathBuf>,
+
+    /// Force key length N, or try every length in MIN-MAX (e.g. 4-12)
+    #[arg(short, long, value_name = "N|MIN-MAX", value_parser = parse_key_len)]
+    pub key_len: Option<RangeInclusive<usize>>,
+
+    /// Number of candidate decryptions to report
+    #[arg(short = 'n', long, value_name = "N", default_value_t = 10, global = true)]
+    pub top: usize,
+
+    /// How candidate decryptions are ranked
+    #[arg(short, long, value_enum, default_value_t = Scorer::Tetragram)]
+    pub scorer: Scorer,
+
+    /// English sample text to measure the English fitness distribution on (default: a built-in passage)
+    #[arg(long, value_name = "FILE")]
+    pub reference: Option<PathBuf>,
+
+    /// How each key letter is found
+    #[arg(long, value_enum, default_value_t = ColumnSolver::Chi)]
+    pub solver: ColumnSolver,
+
+    /// Print one JSON document instead of the text report (no prompts)
+    #[arg(long, global = true)]
+    pub json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Running-key cipher (key as long as the message): encrypt, decrypt or attack
+    RunningKey(RunningKeyArgs),
+    /// Quagmire I-IV (mixed-alphabet Vigenere): encrypt, decrypt or attack
+    Quagmire(QuagmireArgs),
+    /// Drag a probable word along the ciphertext to recover key length and key
+    Crib(CribArgs),
+    /// Try every word of a wordlist as the key, ranked by quadgram fitness
+    Dict(DictArgs),
+    /// Solve by hand in the terminal: per-column frequency bars, live plaintext
+    Tui(TuiArgs),
+    /// Guess the cipher family from ciphertext statistics, then run the matching breaker
+    Identify(IdentifyArgs),
+    /// Homophonic substitution (numeric codes): encrypt, decrypt or attack
+    Homophonic(HomophonicArgs),
+    /// ADFGVX, bifid and trifid: encrypt, decrypt, or attack ADFGVX
+    Fractionate(FractionateArgs),
+    /// Key space, redundancy and unicity distance, checked against the breakers (no input read)
+    Unicity(UnicityArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct RunningKeyArgs {
+    /// Encrypt the input with the key text in FILE instead of attacking
+    #[arg(long, value_name = "FILE", conflicts_with = "decrypt")]
+    pub encrypt: Option<PathBuf>,
+
+    /// Decrypt the input with the key text in FILE instead of attacking
+    #[arg(long, value_name = "FILE")]
+    pub decrypt: Option<PathBuf>,
+
+    /// Skip this many letters of the key text (where in the book the key starts)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub offset: usize,
+
+    /// Beam width of the attack (states kept per position)
+    #[arg(long, value_name = "N", default_value_t = 3000)]
+    pub beam: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct QuagmireArgs {
+    /// 1 keyed plaintext, 2 keyed ciphertext, 3 same keyed alphabet on both, 4 two keyed alphabets
+    #[arg(long, value_name = "1-4", default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=4))]
+    pub variant: u8,
+
+    /// Keyword of the keyed alphabet (the plaintext one for variant 4)
+    #[arg(long, value_name = "WORD")]
+    pub keyword: Option<String>,
+
+    /// Keyword of the ciphertext alphabet (variant 4 only)
+    #[arg(long, value_name = "WORD")]
+    pub ct_keyword: Option<String>,
+
+    /// Indicator key; with it the input is encrypted (or decrypted) instead of attacked
+    #[arg(long, value_name = "KEY")]
+    pub indicator: Option<String>,
+
+    /// Decrypt with the given keywords and indicator instead of encrypting
+    #[arg(long, requires = "indicator")]
+    pub decrypt: bool,
+
+    /// Period for the attack (default: Kasiski + periodic IoC)
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub period: Option<usize>,
+
+    /// Hill-climbing restarts
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    pub restarts: usize,
+
+    /// Hill-climbing steps per restart
+    #[arg(long, value_name = "N", default_value_t = 20000)]
+    pub iterations: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct CribArgs {
+    /// Probable plaintext word (a header, a name, ...)
+    #[arg(long, value_name = "WORD")]
+    pub word: String,
+
+    /// Longest key length to accept from a fragment's period
+    #[arg(long, value_name = "N", default_value_t = 40)]
+    pub max_key_len: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct DictArgs {
+    /// Wordlist, one candidate key per line (read as a stream)
+    #[arg(long, value_name = "FILE")]
+    pub wordlist: PathBuf,
+
+    /// Try words of every length, not just the likely Kasiski key lengths
+    #[arg(long)]
+    pub any_length: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct TuiArgs {
+    /// Key length to start with (default: Kasiski + periodic IoC); +/- changes it
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub key_len: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+pub struct IdentifyArgs {
+    /// Only report the guess; do not run a breaker (implied by --json)
+    #[arg(long)]
+    pub no_break: bool,
+
+    /// Longest key (Vigenere period, XOR key bytes) to look for
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    pub max_key_len: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct HomophonicArgs {
+    /// Encrypt the input with a new random key, saved to FILE
+    #[arg(long, value_name = "FILE", conflicts_with = "decrypt")]
+    pub encrypt: Option<PathBuf>,
+
+    /// Decrypt the input codes with the key in FILE instead of attacking
+    #[arg(long, value_name = "FILE")]
+    pub decrypt: Option<PathBuf>,
+
+    /// Number of codes in a new key (codes 0..N); also sets the width of the codes in
+    /// an unbroken run of digits
+    #[arg(long, value_name = "N", default_value_t = 100, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(26..=10000))]
+    pub symbols: usize,
+
+    /// Attack restarts
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub restarts: usize,
+
+    /// Outer (code count) moves per restart
+    #[arg(long, value_name = "N", default_value_t = 300)]
+    pub iterations: usize,
+}
+
+impl Cli {
+    /// True when ciphertext comes from stdin rather than a file.
+    pub fn reads_stdin(&self) -> bool {
+        match &self.input {
+            None => true,
+            Some(p) => p.as_os_str() == "-",
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct FractionateArgs {
+    /// Which fractionating cipher
+    #[arg(long, value_enum, default_value_t = Fractionating::Adfgvx)]
+    pub cipher: Fractionating,
+
+    /// Keyword of the Polybius square (cube for trifid); with it the input is encrypted
+    /// (or decrypted) instead of attacked
+    #[arg(long, value_name = "WORD")]
+    pub square: Option<String>,
+
+    /// Columnar transposition keyword (ADFGVX)
+    #[arg(long, value_name = "WORD")]
+    pub key: Option<String>,
+
+    /// Decrypt with the given square (and key) instead of encrypting
+    #[arg(long, requires = "square")]
+    pub decrypt: bool,
+
+    /// Bifid/trifid block length; 0 fractionates the whole message at once
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub period: usize,
+
+    /// Widest transposition the ADFGVX attack tries
+    #[arg(long, value_name = "N", default_value_t = 12, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(2..=30))]
+    pub max_width: usize,
+
+    /// Substitution-layer restarts
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub restarts: usize,
+
+    /// Outer moves per substitution restart
+    #[arg(long, value_name = "N", default_value_t = 300)]
+    pub iterations: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct UnicityArgs {
+    /// One cipher only (default: all)
+    #[arg(long, value_enum)]
+    pub cipher: Option<UnicityCipher>,
+
+    /// Key length in bytes (XOR) or letters (Vigenere)
+    #[arg(long, value_name = "N", default_value_t = 5, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub key_len: usize,
+
+    /// Breaker runs per ciphertext length; 0 skips the validation
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    pub trials: usize,
+
+    /// English text to cut validation plaintexts from (default: a built-in passage)
+    #[arg(long, value_name = "FILE")]
+    pub sample: Option<PathBuf>,
+}
+
+/// Ranking used for the final candidate list.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scorer {
+    /// Seed tetragram table (small, illustrative)
+    Tetragram,
+    /// Larger quadgram log-probability table
+    Quadgram,
+    /// Total chi-squared of the column solve (lower is better)
+    Chi,
+    /// Share of letters in dictionary words after segmentation (ties: quadgram fitness)
+    Words,
+}
+
+impl Scorer {
+    pub fn name(self) -> &'static str {
+        match self {
+            Scorer::Tetragram => "tetragram",
+            Scorer::Quadgram => "quadgram",
+            Scorer::Chi => "chi",
+            Scorer::Words => "words",
+        }
+    }
+}
+
+/// How `try_key_length` recovers the key letters.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnSolver {
+    /// Each column on its own: shift with the lowest chi-squared against English
+    Chi,
+    /// Mutual IoC: shifts between columns first, then one unknown letter for the whole key
+    Mic,
+}
+
+impl ColumnSolver {
+    pub fn name(self) -> &'static str {
+        match self {
+            ColumnSolver::Chi => "chi",
+            ColumnSolver::Mic => "mic",
+        }
+    }
+}
+
+/// Fractionating ciphers: a Polybius coordinate system, then the coordinates are shuffled.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fractionating {
+    /// 6x6 square over A-Z0-9 labelled ADFGVX, then columnar transposition
+    Adfgvx,
+    /// 5x5 square (I = J), rows and columns of each block written out separately
+    Bifid,
+    /// 3x3x3 cube over A-Z and '+', layers, rows and columns of each block separately
+    Trifid,
+}
+
+/// Ciphers the unicity calculator knows.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnicityCipher {
+    /// Repeating-key XOR over bytes
+    Xor,
+    /// Vigenere with a known key length
+    Vigenere,
+    /// Simple (monoalphabetic) substitution
+    Substitution,
+}
+
+/// Parse "N" or "MIN-MAX" into an inclusive key-length range.
+fn parse_key_len(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse = |t: &str| {
+        t.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("invalid key length '{}'", t.trim()))
+    };
+    let (lo, hi) = match s.split_once('-') {
+        Some((a, b)) => (parse(a)?, parse(b)?),
+        None => {
+            let k = parse(s)?;
+            (k, k)
+        }
+    };
+    if lo == 0 {
+        return Err("key length must be at least 1".to_string());
+    }
+    if lo > hi {
+        return Err(format!("empty key length range {}-{}", lo, hi));
+    }
+    Ok(lo..=hi)
+}
@@ -0,0 +1,227 @@
+// confidence.rs
+// What the breaker's numbers mean. A chi-squared of 180 or a fitness of -6357 says
+// nothing by itself; here they are turned into probabilities.
+//
+// - Chi-squared: each column contributes 25 degrees of freedom, so the total over k
+//   columns is compared with a chi-squared(25k) distribution. The p-value is the chance
+//   that English text would fit at least this badly. (The shift was chosen to minimise
+//   chi-squared, which biases it low; treat the p-value as optimistic.)
+// - Fitness: the quadgram score divided by the number of quadgrams is compared with the
+//   per-quadgram score of random letters and of English. For random letters the mean and
+//   spread are exact (every quadgram equally likely). For English they are measured on a
+//   reference text: --reference, or a short built-in passage. (The table's own expected
+//   score is no use here: real text always scores worse than the text a table was
+//   built from.) Over m quadgrams the mean has spread sigma / sqrt(m), treating
+//   quadgrams as independent, which overlapping windows are not quite.
+//
+// A candidate is "english" when English is far more likely than random and its score is
+// within 4 spreads of English (a little slack for the independence assumption and for
+// texts unlike the reference); "random" when random letters are far more likely;
+// otherwise "partial" (typically most key letters right, a few wrong).
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::ngrams::{dense_quadgrams, QUADGRAM_FLOOR};
+use crate::{clean_text, quadgram_score};
+
+/// Default English reference: the Gettysburg Address and the opening of Pride and Prejudice
+pub const BUILTIN_REFERENCE: &str = "
+Four score and seven years ago our fathers brought forth on this continent, a new nation,
+conceived in Liberty, and dedicated to the proposition that all men are created equal.
+Now we are engaged in a great civil war, testing whether that nation, or any nation so
+conceived and so dedicated, can long endure. We are met on a great battle-field of that war.
+We have come to dedicate a portion of that field, as a final resting place for those who here
+gave their lives that that nation might live. It is altogether fitting and proper that we
+should do this. But, in a larger sense, we can not dedicate -- we can not consecrate -- we can
+not hallow -- this ground. The brave men, living and dead, who struggled here, have
+consecrated it, far above our poor power to add or detract. The world will little note, nor
+long remember what we say here, but it can never forget what they did here. It is for us the
+living, rather, to be dedicated here to the unfinished work which they who fought here have
+thus far so nobly advanced. It is rather for us to be here dedicated to the great task
+remaining before us -- that from these honored dead we take increased devotion to that cause
+for which they gave the last full measure of devotion -- that we here highly resolve that
+these dead shall not have died in vain -- that this nation, under God, shall have a new birth
+of freedom -- and that government of the people, by the people, for the people, shall not
+perish from the earth.
+It is a truth universally acknowledged, that a single man in possession of a good fortune,
+must be in want of a wife. However little known the feelings or views of such a man may be
+on his first entering a neighbourhood, this truth is so well fixed in the minds of the
+surrounding families, that he is considered the rightful property of some one or other of
+their daughters. My dear Mr. Bennet, said his lady to him one day, have you heard that
+Netherfield Park is let at last? Mr. Bennet replied that he had not. But it is, returned she;
+for Mrs. Long has just been here, and she told me all about it. Mr. Bennet made no answer.
+Do you not want to know who has taken it? cried his wife impatiently. You want to tell me,
+and I have no objection to hearing it. This was invitation enough.
+";
+
+/// Mean and standard deviation of the score of one quadgram.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Baseline {
+    pub mean: f64,
+    pub sd: f64,
+}
+
+/// Reference distributions a candidate is compared against.
+#[derive(Serialize, Debug, Clone)]
+pub struct Calibration {
+    pub random: Baseline,
+    pub english: Baseline,
+    /// "reference text" or "built-in passage"
+    pub english_source: &'static str,
+}
+
+/// Calibrated reading of one candidate's statistics.
+#[derive(Serialize, Debug, Clone)]
+pub struct Confidence {
+    pub chi_squared_p_value: f64,
+    pub fitness_per_quadgram: f64,
+    /// Spreads above the random-letter mean
+    pub z_vs_random: f64,
+    /// Spreads above (negative: below) the English mean
+    pub z_vs_english: f64,
+    /// Posterior probability of English vs random letters, equal priors
+    pub english_probability: f64,
+    /// "english", "partial" or "random"
+    pub verdict: &'static str,
+}
+
+/// Baselines for this quadgram table; `reference` is English text (letters only).
+pub fn calibrate(quadgrams: &HashMap<[u8; 4], f64>, reference: Option<&str>) -> Calibration {
+    let dense = dense_quadgrams(quadgrams);
+    let random = mean_sd(dense.iter().map(|&l| (1.0, l)));
+
+    let builtin;
+    let (text, english_source) = match reference {
+        Some(t) if t.len() >= 4 => (t, "reference text"),
+        _ => {
+            builtin = clean_text(BUILTIN_REFERENCE);
+            (builtin.as_str(), "built-in passage")
+        }
+    };
+    let scores: Vec<f64> = text
+        .as_bytes()
+        .windows(4)
+        .map(|w| quadgrams.get(&[w[0], w[1], w[2], w[3]]).copied().unwrap_or(QUADGRAM_FLOOR))
+        .collect();
+    let english = mean_sd(scores.iter().map(|&l| (1.0, l)));
+    Calibration { random, english, english_source }
+}
+
+/// Weighted mean and standard deviation of (weight, value) pairs
+fn mean_sd(items: impl Iterator<Item = (f64, f64)> + Clone) -> Baseline {
+    let total: f64 = items.clone().map(|(w, _)| w).sum();
+    let mean = items.clone().map(|(w, x)| w * x).sum::<f64>() / total;
+    let var = items.map(|(w, x)| w * (x - mean).powi(2)).sum::<f64>() / total;
+    Baseline { mean, sd: var.sqrt() }
+}
+
+/// Read one candidate: its plaintext, total chi-squared and key length.
+pub fn assess(cal: &Calibration, plaintext: &str, quadgrams: &HashMap<[u8; 4], f64>, chi_squared: f64, key_length: usize) -> Confidence {
+    let m = plaintext.len().saturating_sub(3).max(1) as f64;
+    let per_quadgram = quadgram_score(plaintext, quadgrams) / m;
+    let spread = |b: Baseline| (b.sd / m.sqrt()).max(1e-12);
+    let z_vs_random = (per_quadgram - cal.random.mean) / spread(cal.random);
+    let z_vs_english = (per_quadgram - cal.english.mean) / spread(cal.english);
+
+    // Normal log-likelihoods of the mean score under each hypothesis
+    let log_lik = |z: f64, b: Baseline| -0.5 * z * z - spread(b).ln();
+    let diff = log_lik(z_vs_random, cal.random) - log_lik(z_vs_english, cal.english);
+    let english_probability = 1.0 / (1.0 + diff.exp());
+
+    let verdict = if english_probability > 0.99 && z_vs_english > -4.0 {
+        "english"
+    } else if english_probability < 0.01 {
+        "random"
+    } else {
+        "partial"
+    };
+
+    Confidence {
+        chi_squared_p_value: chi_squared_p_value(chi_squared, 25 * key_length),
+        fitness_per_quadgram: per_quadgram,
+        z_vs_random,
+        z_vs_english,
+        english_probability,
+        verdict,
+    }
+}
+
+/// P(X >= chi) for X ~ chi-squared with `df` degrees of freedom
+pub fn chi_squared_p_value(chi: f64, df: usize) -> f64 {
+    if !chi.is_finite() {
+        return 0.0;
+    }
+    upper_gamma_q(df as f64 / 2.0, chi / 2.0)
+}
+
+/// Regularised upper incomplete gamma Q(a, x): series below a + 1, continued fraction above
+fn upper_gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefix = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        // P(a, x) = e^-x x^a / Gamma(a+1) * sum x^n / ((a+1)...(a+n))
+        let (mut term, mut sum, mut ap) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..10_000 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        (1.0 - sum * log_prefix.exp()).clamp(0.0, 1.0)
+    } else {
+        // Lentz's method for the continued fraction of Q(a, x)
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..10_000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (log_prefix.exp() * h).clamp(0.0, 1.0)
+    }
+}
+
+/// ln Gamma(x) for x > 0 (Lanczos, g = 7)
+fn ln_gamma(x: f64) -> f64 {
+    const G: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection: Gamma(x) Gamma(1-x) = pi / sin(pi x)
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = G[1..].iter().enumerate().fold(G[0], |s, (i, &g)| s + g / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
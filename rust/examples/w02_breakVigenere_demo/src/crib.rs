@@ -0,0 +1,77 @@
+// crib.rs
+// Crib dragging: slide a probable word along the ciphertext. At each offset,
+// ciphertext - crib (i.e. `vigenere_decrypt` with the crib as key) is the key
+// fragment that would be needed there. A true hit exposes a *periodic* fragment
+// whenever the crib is longer than the key, which gives both key length and key;
+// the full decryption then decides between offsets.
+
+use std::collections::HashMap;
+
+use crate::cli::{Cli, CribArgs};
+use crate::report::{print_json, CribCandidate, CribReport};
+use crate::segment::Segmenter;
+use crate::{clean_text, quadgram_score, vigenere_decrypt};
+
+/// Entry point for `crib`.
+pub fn run(cli: &Cli, args: &CribArgs, cipher: &str, quadgrams: &HashMap<[u8; 4], f64>, words: &Segmenter) {
+    let crib = clean_text(&args.word);
+    if crib.len() < 2 || crib.len() > cipher.len() {
+        eprintln!("Crib must have 2..={} letters after cleaning", cipher.len());
+        std::process::exit(1);
+    }
+
+    let offsets_tried = cipher.len() - crib.len() + 1;
+    let mut candidates: Vec<CribCandidate> = Vec::new();
+    for offset in 0..offsets_tried {
+        let fragment = vigenere_decrypt(&cipher[offset..offset + crib.len()], &crib);
+        let Some(period) = fragment_period(fragment.as_bytes(), args.max_key_len) else {
+            continue;
+        };
+        // fragment[i] is key letter number (offset + i) mod period
+        let mut key = vec![b'A'; period];
+        for (i, &k) in fragment.as_bytes()[..period].iter().enumerate() {
+            key[(offset + i) % period] = k;
+        }
+        let key = String::from_utf8(key).expect("key is ASCII");
+        let plaintext = vigenere_decrypt(cipher, &key);
+        let (segmented, word_coverage) = words.readable(&plaintext);
+        candidates.push(CribCandidate {
+            offset,
+            key_fragment: fragment,
+            key_length: period,
+            confirmations: crib.len() - period,
+            fitness: quadgram_score(&plaintext, quadgrams),
+            key,
+            word_coverage,
+            plaintext,
+            segmented,
+        });
+    }
+
+    candidates.sort_by(|a, b| b.fitness.total_cmp(&a.fitness).then_with(|| b.confirmations.cmp(&a.confirmations)));
+    let periodic_offsets = candidates.len();
+    candidates.truncate(cli.top);
+    let report = CribReport { crib, offsets_tried, periodic_offsets, candidates };
+
+    if cli.json {
+        print_json(&report);
+        return;
+    }
+    println!("Crib {} dragged over {} offsets; {} give a periodic key fragment\n", report.crib, report.offsets_tried, report.periodic_offsets);
+    for c in report.candidates.iter() {
+        println!(
+            "offset {:4} | fragment {} | key len {:2} ({} confirming letters) | fitness {:8.2} | words {:3.0}% | key: {}",
+            c.offset, c.key_fragment, c.key_length, c.confirmations, c.fitness, 100.0 * c.word_coverage, c.key
+        );
+        println!("Plaintext:\n{}\n", c.segmented);
+    }
+    if report.candidates.is_empty() {
+        println!("No offset gives a periodic fragment: the key may be longer than the crib.");
+        println!("Try a longer crib or a larger --max-key-len.");
+    }
+}
+
+/// Smallest period (< fragment length, <= max) the fragment repeats with, if any
+fn fragment_period(fragment: &[u8], max: usize) -> Option<usize> {
+    (1..fragment.len().min(max + 1)).find(|&p| fragment.iter().zip(&fragment[p..]).all(|(a, b)| a == b))
+}
@@ -0,0 +1,119 @@
+// dict.rs
+// Dictionary attack: real keys are usually words. Stream a wordlist, keep the words whose
+// length is a likely key length (Kasiski factors), decrypt with each and rank by quadgram
+// fitness. On short ciphertexts this beats the per-column chi-squared solve, which has
+// too few letters per column to be reliable.
+//
+// Quadgram log-probabilities are all <= 0, so a running score only goes down: once it
+// drops below the worst score still in the top list, the word is abandoned.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use crate::cli::{Cli, DictArgs};
+use crate::ngrams::note_if_builtin;
+use crate::report::{print_json, sorted_factors, DictCandidate, DictReport};
+use crate::segment::Segmenter;
+use crate::{
+    clean_text, collect_distances, factor_frequencies, find_repeats, kasiski_key_lengths,
+    quadgram_score_bounded, vigenere_decrypt, MIN_NGRAM,
+};
+
+/// Entry point for `dict`.
+pub fn run(cli: &Cli, args: &DictArgs, cipher: &str, quadgrams: &HashMap<[u8; 4], f64>, words: &Segmenter) {
+    // Candidate key lengths: the most common Kasiski factors (none found -> no filter)
+    let key_lengths = if args.any_length {
+        Vec::new()
+    } else {
+        let factors = sorted_factors(factor_frequencies(&collect_distances(&find_repeats(cipher, MIN_NGRAM))));
+        kasiski_key_lengths(&factors, 0)
+    };
+
+    let mut report = match attack(cipher, &args.wordlist, &key_lengths, quadgrams, cli.top.max(1)) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to read wordlist {}: {}", args.wordlist.display(), e);
+            std::process::exit(1);
+        }
+    };
+    // Segment only the survivors, not every word tried
+    for c in report.candidates.iter_mut() {
+        (c.segmented, c.word_coverage) = words.readable(&c.plaintext);
+    }
+
+    if cli.json {
+        print_json(&report);
+        return;
+    }
+    if report.key_lengths.is_empty() {
+        println!("Key lengths: any");
+    } else {
+        println!("Key lengths (Kasiski factors): {:?}", report.key_lengths);
+    }
+    println!(
+        "Words read: {} | tried: {} | abandoned early: {}\n",
+        report.words_read, report.words_tried, report.words_abandoned
+    );
+    for (i, c) in report.candidates.iter().enumerate() {
+        println!("#{:<2} fitness {:9.2} | words {:3.0}% | key: {}", i + 1, c.fitness, 100.0 * c.word_coverage, c.key);
+        println!("Plaintext:\n{}\n", c.segmented);
+    }
+    if report.candidates.is_empty() {
+        println!("No word of a candidate length in the list; try --any-length.");
+    }
+    note_if_builtin(quadgrams);
+}
+
+/// Try every word of an accepted length (all lengths if `key_lengths` is empty), keep the `top` best.
+fn attack(
+    cipher: &str,
+    wordlist: &std::path::Path,
+    key_lengths: &[usize],
+    quadgrams: &HashMap<[u8; 4], f64>,
+    top: usize,
+) -> io::Result<DictReport> {
+    let mut reader = BufReader::new(File::open(wordlist)?);
+    let mut line: Vec<u8> = Vec::new();
+    let (mut words_read, mut words_tried, mut words_abandoned) = (0, 0, 0);
+    // Best candidates so far, highest fitness first
+    let mut best: Vec<DictCandidate> = Vec::with_capacity(top + 1);
+
+    loop {
+        line.clear();
+        // Wordlists are not always valid UTF-8; only the ASCII letters matter anyway
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        let key = clean_text(&String::from_utf8_lossy(&line));
+        if key.is_empty() {
+            continue;
+        }
+        words_read += 1;
+        if !key_lengths.is_empty() && !key_lengths.contains(&key.len()) {
+            continue;
+        }
+        if best.iter().any(|c| c.key == key) {
+            continue;
+        }
+        words_tried += 1;
+
+        let bound = if best.len() == top { best[top - 1].fitness } else { f64::NEG_INFINITY };
+        let plaintext = vigenere_decrypt(cipher, &key);
+        let Some(fitness) = quadgram_score_bounded(&plaintext, quadgrams, bound) else {
+            words_abandoned += 1;
+            continue;
+        };
+        let at = best.partition_point(|c| c.fitness >= fitness);
+        best.insert(at, DictCandidate { key, fitness, word_coverage: 0.0, plaintext, segmented: String::new() });
+        best.truncate(top);
+    }
+
+    Ok(DictReport {
+        key_lengths: key_lengths.to_vec(),
+        words_read,
+        words_tried,
+        words_abandoned,
+        candidates: best,
+    })
+}
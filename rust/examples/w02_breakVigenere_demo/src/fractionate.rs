@@ -0,0 +1,373 @@
+// fractionate.rs
+// Fractionating ciphers: each letter becomes coordinates in a keyed Polybius square, and
+// the coordinates, not the letters, are shuffled - so one ciphertext symbol carries half
+// (or a third) of a plaintext letter.
+//
+//   ADFGVX  6x6 square over A-Z0-9, rows and columns labelled A D F G V X; the label
+//           pairs are then written in rows under a keyword and read off by columns in
+//           alphabetical order of the keyword (columnar transposition)
+//   bifid   5x5 square (I = J); in each block of `period` letters all row numbers are
+//           written out, then all column numbers, and the stream is re-read in pairs
+//   trifid  3x3x3 cube over A-Z and '+', the same with layers, rows and columns
+//
+// ADFGVX attack: a transposition only moves symbols, so undoing it with the right width
+// and column order brings back the label pairs of the square. Those pairs are a simple
+// substitution of English, so their IoC over the 36 possible pairs is ~0.066; pairs of
+// unrelated labels give far less. Climbing on that pair IoC per trial width recovers
+// the width and which columns belong together. For an even width, whole pairs of
+// columns can still be in the wrong order (the pair IoC cannot see it); the IoC of
+// *consecutive* pairs of pairs puts them in order. The substitution layer that remains
+// is solved by the homophonic breaker's climb on quadgrams. Quadgrams only know letters,
+// so the digits of the square are not recovered: a digit in the plaintext comes out as
+// whatever letter fits best there, and its cell shows that letter.
+
+use std::collections::HashMap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::cli::{Cli, FractionateArgs, Fractionating};
+use crate::homophonic::climb_codes;
+use crate::ngrams::{dense_quadgrams, note_if_builtin};
+use crate::report::{print_json, AdfgvxCandidate, AdfgvxReport, WidthScore};
+use crate::segment::Segmenter;
+use crate::clean_text;
+
+const ADFGVX_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const ADFGVX_LABELS: &[u8] = b"ADFGVX";
+const BIFID_ALPHABET: &[u8] = b"ABCDEFGHIKLMNOPQRSTUVWXYZ";
+const TRIFID_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ+";
+
+/// Column orders tried per width in the ADFGVX attack
+const ORDER_RESTARTS: usize = 10;
+
+/// Entry point for `fractionate`; `input` is raw text (ADFGVX plaintext may hold digits,
+/// trifid uses '+').
+pub fn run(cli: &Cli, args: &FractionateArgs, input: &str, quadgrams: &HashMap<[u8; 4], f64>, words: &Segmenter) {
+    if let Some(keyword) = &args.square {
+        let out = match args.cipher {
+            Fractionating::Adfgvx => {
+                let key = clean_text(args.key.as_deref().unwrap_or(""));
+                if key.is_empty() {
+                    eprintln!("ADFGVX needs a transposition --key");
+                    std::process::exit(1);
+                }
+                let square = keyed(keyword, ADFGVX_ALPHABET);
+                if args.decrypt {
+                    adfgvx_decrypt(&keep(input, ADFGVX_LABELS), &square, key.as_bytes())
+                } else {
+                    adfgvx_encrypt(&keep(input, ADFGVX_ALPHABET), &square, key.as_bytes())
+                }
+            }
+            Fractionating::Bifid => polybius(input, keyword, BIFID_ALPHABET, 2, args.period, args.decrypt),
+            Fractionating::Trifid => polybius(input, keyword, TRIFID_ALPHABET, 3, args.period, args.decrypt),
+        };
+        let label = if args.decrypt { "plaintext" } else { "ciphertext" };
+        let out = String::from_utf8(out).expect("alphabets are ASCII");
+        if cli.json {
+            print_json(&serde_json::json!({ label: out }));
+        } else {
+            println!("{}", out);
+        }
+        return;
+    }
+
+    if args.cipher != Fractionating::Adfgvx {
+        eprintln!("Only ADFGVX can be attacked here; pass --square to encrypt or decrypt bifid/trifid.");
+        std::process::exit(1);
+    }
+    let cipher: Vec<u8> = keep(input, ADFGVX_LABELS)
+        .iter()
+        .map(|c| ADFGVX_LABELS.iter().position(|l| l == c).expect("kept labels only") as u8)
+        .collect();
+    if cipher.len() < 12 || cipher.len() % 2 == 1 {
+        eprintln!("ADFGVX ciphertext needs an even number of at least 12 A/D/F/G/V/X: got {}", cipher.len());
+        std::process::exit(1);
+    }
+    let report = attack(&cipher, args, quadgrams, cli.top);
+
+    if cli.json {
+        print_json(&report);
+        return;
+    }
+    println!("Pair IoC by transposition width (Polybius pairs ~0.066, unrelated labels less):");
+    for w in report.widths.iter() {
+        let mark = if w.width == report.width { "  <-" } else { "" };
+        println!("  {:2}: {:.4}{}", w.width, w.digraph_ioc, mark);
+    }
+    println!("Column order (ciphertext column read into each plaintext column): {:?}\n", report.column_order);
+    for c in report.candidates.iter() {
+        println!("fitness {:9.2}", c.fitness);
+        println!("Square (rows and columns A D F G V X):");
+        for row in c.square.as_bytes().chunks(6) {
+            println!("  {}", String::from_utf8_lossy(row));
+        }
+        println!("Plaintext:\n{}\n", words.readable(&c.plaintext).0);
+    }
+    println!(
+        "Note: only the 26 letters of the square are recovered; digits come out as letters.{}",
+        if report.distinct_pairs > 26 {
+            format!(" {} distinct pairs were seen, so the plaintext held digits.", report.distinct_pairs)
+        } else {
+            String::new()
+        }
+    );
+    note_if_builtin(quadgrams);
+}
+
+/// Upper-cased input reduced to `alphabet` (J read as I when the alphabet has no J)
+fn keep(input: &str, alphabet: &[u8]) -> Vec<u8> {
+    let merge_j = !alphabet.contains(&b'J');
+    input
+        .bytes()
+        .map(|b| b.to_ascii_uppercase())
+        .map(|b| if merge_j && b == b'J' { b'I' } else { b })
+        .filter(|b| alphabet.contains(b))
+        .collect()
+}
+
+/// Keyword letters first (repeats dropped), then the rest of the alphabet in order
+fn keyed(keyword: &str, alphabet: &[u8]) -> Vec<u8> {
+    let mut square: Vec<u8> = Vec::with_capacity(alphabet.len());
+    for b in keep(keyword, alphabet).into_iter().chain(alphabet.iter().copied()) {
+        if !square.contains(&b) {
+            square.push(b);
+        }
+    }
+    square
+}
+
+fn adfgvx_encrypt(plain: &[u8], square: &[u8], key: &[u8]) -> Vec<u8> {
+    let pairs: Vec<u8> = plain
+        .iter()
+        .flat_map(|c| {
+            let i = square.iter().position(|s| s == c).expect("plaintext filtered to the square");
+            [ADFGVX_LABELS[i / 6], ADFGVX_LABELS[i % 6]]
+        })
+        .collect();
+    let w = key.len();
+    column_order(key)
+        .into_iter()
+        .flat_map(|col| pairs.iter().skip(col).step_by(w).copied().collect::<Vec<u8>>())
+        .collect()
+}
+
+fn adfgvx_decrypt(cipher: &[u8], square: &[u8], key: &[u8]) -> Vec<u8> {
+    let symbols: Vec<u8> = cipher.iter().map(|c| ADFGVX_LABELS.iter().position(|l| l == c).expect("labels") as u8).collect();
+    // The ciphertext column read into plaintext column j is the rank of j in the key
+    let mut perm = vec![0; key.len()];
+    for (rank, col) in column_order(key).into_iter().enumerate() {
+        perm[col] = rank;
+    }
+    read_rows(&symbols, &perm).chunks_exact(2).map(|p| square[p[0] as usize * 6 + p[1] as usize]).collect()
+}
+
+/// Plaintext columns in the order they are read out: alphabetical by key letter, ties left to right
+fn column_order(key: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..key.len()).collect();
+    order.sort_by_key(|&i| (key[i], i));
+    order
+}
+
+/// Undo a columnar transposition: `perm[j]` is the ciphertext column holding plaintext
+/// column j. The first `len % width` plaintext columns are one symbol longer.
+fn read_rows(cipher: &[u8], perm: &[usize]) -> Vec<u8> {
+    let w = perm.len();
+    let (rows, long) = (cipher.len() / w, cipher.len() % w);
+    let mut plain_col = vec![0; w];
+    for (j, &c) in perm.iter().enumerate() {
+        plain_col[c] = j;
+    }
+    let mut start = vec![0; w];
+    let mut at = 0;
+    for c in 0..w {
+        start[c] = at;
+        at += rows + usize::from(plain_col[c] < long);
+    }
+    let mut out = Vec::with_capacity(cipher.len());
+    for row in 0..=rows {
+        for (j, &c) in perm.iter().enumerate() {
+            if row < rows || j < long {
+                out.push(cipher[start[c] + row]);
+            }
+        }
+    }
+    out
+}
+
+/// Bifid (2 coordinates, base 5) or trifid (3 coordinates, base 3) in either direction
+fn polybius(input: &str, keyword: &str, alphabet: &[u8], dims: usize, period: usize, decrypt: bool) -> Vec<u8> {
+    let square = keyed(keyword, alphabet);
+    let base = if dims == 2 { 5 } else { 3 };
+    let idx: Vec<usize> = keep(input, alphabet).iter().map(|c| square.iter().position(|s| s == c).expect("filtered")).collect();
+    let block = if period == 0 { idx.len().max(1) } else { period };
+    idx.chunks(block)
+        .flat_map(|chunk| if decrypt { unfractionate(chunk, base, dims) } else { fractionate(chunk, base, dims) })
+        .map(|i| square[i])
+        .collect()
+}
+
+/// Coordinate k of cell `i`, most significant first
+fn digit(i: usize, base: usize, dims: usize, k: usize) -> usize {
+    i / base.pow((dims - 1 - k) as u32) % base
+}
+
+fn from_digits(d: &[usize], base: usize) -> usize {
+    d.iter().fold(0, |acc, &x| acc * base + x)
+}
+
+/// Write coordinate 0 of every cell in the block, then coordinate 1, ...; re-read in groups
+fn fractionate(block: &[usize], base: usize, dims: usize) -> Vec<usize> {
+    let stream: Vec<usize> = (0..dims).flat_map(|k| block.iter().map(move |&i| digit(i, base, dims, k))).collect();
+    stream.chunks(dims).map(|d| from_digits(d, base)).collect()
+}
+
+fn unfractionate(block: &[usize], base: usize, dims: usize) -> Vec<usize> {
+    let stream: Vec<usize> = block.iter().flat_map(|&i| (0..dims).map(move |k| digit(i, base, dims, k))).collect();
+    let n = block.len();
+    (0..n)
+        .map(|i| from_digits(&(0..dims).map(|k| stream[k * n + i]).collect::<Vec<usize>>(), base))
+        .collect()
+}
+
+/// Width and column order from pair statistics, then the substitution layer.
+fn attack(cipher: &[u8], args: &FractionateArgs, quadgrams: &HashMap<[u8; 4], f64>, top: usize) -> AdfgvxReport {
+    let mut rng = rand::thread_rng();
+    let mut best: Option<(usize, Vec<usize>, f64)> = None;
+    let mut widths = Vec::new();
+    for w in 2..=args.max_width.min(cipher.len() / 4) {
+        let (perm, ioc) = (0..ORDER_RESTARTS)
+            .map(|_| climb_order(cipher, w, &mut rng))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("at least one restart");
+        widths.push(WidthScore { width: w, digraph_ioc: ioc });
+        // A wider grid has more freedom to overfit: demand a clear gain
+        if best.as_ref().is_none_or(|b| ioc > b.2 * 1.05) {
+            best = Some((w, perm, ioc));
+        }
+    }
+    let (width, mut perm, _) = best.expect("at least one width");
+    if width % 2 == 0 {
+        order_pairs(cipher, &mut perm);
+    }
+
+    // Each label pair is one code of a simple substitution
+    let pairs: Vec<usize> = read_rows(cipher, &perm).chunks_exact(2).map(|p| p[0] as usize * 6 + p[1] as usize).collect();
+    let mut ids: HashMap<usize, usize> = HashMap::new();
+    let mut cells: Vec<usize> = Vec::new();
+    let codes: Vec<usize> = pairs
+        .iter()
+        .map(|&d| {
+            *ids.entry(d).or_insert_with(|| {
+                cells.push(d);
+                cells.len() - 1
+            })
+        })
+        .collect();
+    let dense = dense_quadgrams(quadgrams);
+    let mut candidates: Vec<AdfgvxCandidate> = (0..args.restarts.max(1))
+        .map(|_| {
+            let (letters, fitness) = climb_codes(&codes, cells.len(), &dense, args.iterations, &mut rng);
+            let mut square = vec![b'?'; 36];
+            for (code, &cell) in cells.iter().enumerate() {
+                square[cell] = b'A' + letters[code];
+            }
+            AdfgvxCandidate {
+                fitness,
+                square: String::from_utf8(square).expect("ASCII"),
+                plaintext: codes.iter().map(|&c| (b'A' + letters[c]) as char).collect(),
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    candidates.dedup_by(|a, b| a.plaintext == b.plaintext);
+    candidates.truncate(top);
+
+    AdfgvxReport { width, widths, column_order: perm, distinct_pairs: cells.len(), candidates }
+}
+
+/// IoC of the label pairs (36 possible) after undoing the transposition
+fn pair_ioc(rows: &[u8]) -> f64 {
+    let mut counts = [0usize; 36];
+    for p in rows.chunks_exact(2) {
+        counts[p[0] as usize * 6 + p[1] as usize] += 1;
+    }
+    ioc_of(&counts)
+}
+
+/// IoC of consecutive pairs of label pairs (36 x 36 possible)
+fn pair_bigram_ioc(rows: &[u8]) -> f64 {
+    let pairs: Vec<usize> = rows.chunks_exact(2).map(|p| p[0] as usize * 6 + p[1] as usize).collect();
+    let mut counts = vec![0usize; 36 * 36];
+    for w in pairs.windows(2) {
+        counts[w[0] * 36 + w[1]] += 1;
+    }
+    ioc_of(&counts)
+}
+
+fn ioc_of(counts: &[usize]) -> f64 {
+    let n: usize = counts.iter().sum();
+    if n < 2 {
+        return 0.0;
+    }
+    counts.iter().map(|&c| c * c.saturating_sub(1)).sum::<usize>() as f64 / (n * (n - 1)) as f64
+}
+
+/// Column order for width `w` maximising the pair IoC: swap two columns while it helps
+fn climb_order<R: Rng>(cipher: &[u8], w: usize, rng: &mut R) -> (Vec<usize>, f64) {
+    let mut perm: Vec<usize> = (0..w).collect();
+    perm.shuffle(rng);
+    let mut score = pair_ioc(&read_rows(cipher, &perm));
+    loop {
+        let mut improved = false;
+        for i in 0..w {
+            for j in (i + 1)..w {
+                perm.swap(i, j);
+                let s = pair_ioc(&read_rows(cipher, &perm));
+                if s > score + 1e-12 {
+                    score = s;
+                    improved = true;
+                } else {
+                    perm.swap(i, j);
+                }
+            }
+        }
+        if !improved {
+            return (perm, score);
+        }
+    }
+}
+
+/// Even width: move whole column pairs (long with long, short with short) while the IoC
+/// of consecutive label pairs rises
+fn order_pairs(cipher: &[u8], perm: &mut [usize]) {
+    let blocks = perm.len() / 2;
+    let long_blocks = cipher.len() % perm.len() / 2;
+    let mut score = pair_bigram_ioc(&read_rows(cipher, perm));
+    loop {
+        let mut improved = false;
+        for a in 0..blocks {
+            for b in (a + 1)..blocks {
+                if (a < long_blocks) != (b < long_blocks) {
+                    continue;
+                }
+                swap_blocks(perm, a, b);
+                let s = pair_bigram_ioc(&read_rows(cipher, perm));
+                if s > score + 1e-12 {
+                    score = s;
+                    improved = true;
+                } else {
+                    swap_blocks(perm, a, b);
+                }
+            }
+        }
+        if !improved {
+            return;
+        }
+    }
+}
+
+fn swap_blocks(perm: &mut [usize], a: usize, b: usize) {
+    perm.swap(2 * a, 2 * b);
+    perm.swap(2 * a + 1, 2 * b + 1);
+}
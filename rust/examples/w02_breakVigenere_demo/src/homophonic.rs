@@ -0,0 +1,454 @@
+// homophonic.rs
+// Homophonic substitution: every plaintext letter owns several numeric codes, roughly in
+// proportion to its English frequency (E gets ~13 of 100, Z gets one), and each
+// occurrence is enciphered with a random one of them. All codes then appear about
+// equally often, so the ciphertext has no frequency peaks: letter counting, IoC and the
+// chi-squared fit of `best_shift_for_column` have nothing to grip.
+//
+// What survives is the *order* of letters. The breaker climbs on quadgram fitness of the
+// decryption, nested two levels deep (after Dhavare, Low and Stamp):
+//   outer: how many codes each letter owns - move one code to another letter
+//   inner: which codes those are, for the current counts - swap the letters of two codes
+// Each outer move is followed by a full inner climb and kept only if the result is better.
+
+use std::collections::HashMap;
+use std::fs;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::cli::{Cli, HomophonicArgs};
+use crate::ngrams::{dense_quadgrams, note_if_builtin, quad_index};
+use crate::report::{print_json, HomophonicCandidate, HomophonicReport};
+use crate::segment::Segmenter;
+use crate::{clean_text, EN_FREQ};
+
+/// Entry point for `homophonic`; `input` is raw text (codes are digits, not letters).
+pub fn run(cli: &Cli, args: &HomophonicArgs, input: &str, quadgrams: &HashMap<[u8; 4], f64>, words: &Segmenter) {
+    if let Some(path) = &args.encrypt {
+        let plain = clean_text(input);
+        let key = generate_key(args.symbols, &mut rand::thread_rng());
+        if let Err(e) = fs::write(path, format_key(&key)) {
+            eprintln!("Failed to write key {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+        let codes = encrypt(&plain, &key, &mut rand::thread_rng());
+        let width = code_width(args.symbols);
+        let out: Vec<String> = codes.iter().map(|c| format!("{:0width$}", c)).collect();
+        if cli.json {
+            print_json(&serde_json::json!({ "ciphertext": out.join(" ") }));
+        } else {
+            println!("{}", out.join(" "));
+            let letters = crate::index_of_coincidence(plain.as_bytes());
+            eprintln!(
+                "IoC: plaintext letters {:.4}, ciphertext codes {:.4} (flat over {} codes: {:.4})",
+                letters, symbol_ioc(&codes), args.symbols, 1.0 / args.symbols as f64
+            );
+        }
+        return;
+    }
+
+    let codes = match parse_codes(input, code_width(args.symbols)) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Bad ciphertext: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(path) = &args.decrypt {
+        let key = match fs::read_to_string(path).map(|t| parse_key(&t)) {
+            Ok(Ok(k)) => k,
+            Ok(Err(e)) => {
+                eprintln!("Bad key file {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Failed to read key {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        };
+        let plain: String = codes.iter().map(|c| key.get(c).copied().unwrap_or('?')).collect();
+        if cli.json {
+            print_json(&serde_json::json!({ "plaintext": plain }));
+        } else {
+            println!("{}", plain);
+        }
+        return;
+    }
+
+    if codes.len() < 6 {
+        eprintln!("Ciphertext too short to attack: {} codes", codes.len());
+        std::process::exit(1);
+    }
+
+    // Attack. Codes are renumbered 0..distinct for the climb.
+    let mut ids: HashMap<u32, usize> = HashMap::new();
+    let mut distinct: Vec<u32> = Vec::new();
+    let cipher: Vec<usize> = codes
+        .iter()
+        .map(|&c| {
+            *ids.entry(c).or_insert_with(|| {
+                distinct.push(c);
+                distinct.len() - 1
+            })
+        })
+        .collect();
+    let dense = dense_quadgrams(quadgrams);
+    let mut rng = rand::thread_rng();
+
+    let mut candidates: Vec<HomophonicCandidate> = (0..args.restarts.max(1))
+        .map(|_| {
+            let (letters, fitness) = climb_codes(&cipher, distinct.len(), &dense, args.iterations, &mut rng);
+            let plaintext: String = cipher.iter().map(|&s| (b'A' + letters[s]) as char).collect();
+            let mut key: Vec<String> = Vec::new();
+            for letter in 0..26u8 {
+                let mut owned: Vec<u32> = (0..distinct.len()).filter(|&s| letters[s] == letter).map(|s| distinct[s]).collect();
+                owned.sort_unstable();
+                if !owned.is_empty() {
+                    let list: Vec<String> = owned.iter().map(|c| c.to_string()).collect();
+                    key.push(format!("{} {}", (b'A' + letter) as char, list.join(" ")));
+                }
+            }
+            HomophonicCandidate { fitness, key, plaintext }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    candidates.dedup_by(|a, b| a.plaintext == b.plaintext);
+    candidates.truncate(cli.top);
+
+    let counts = code_counts(&codes);
+    let report = HomophonicReport {
+        ciphertext_length: codes.len(),
+        distinct_codes: distinct.len(),
+        code_ioc: symbol_ioc(&codes),
+        top_code_share: counts.values().copied().max().unwrap_or(0) as f64 / codes.len() as f64,
+        candidates,
+    };
+    if cli.json {
+        print_json(&report);
+        return;
+    }
+    println!("Homophonic attack: {} codes, {} distinct", report.ciphertext_length, report.distinct_codes);
+    println!(
+        "Code IoC {:.4} and the commonest code is {:.1}% of the text (English: IoC ~0.066, E ~12.7%):",
+        report.code_ioc, 100.0 * report.top_code_share
+    );
+    println!("the frequencies are flat, so chi-squared against EN_FREQ has nothing to fit.\n");
+    for c in report.candidates.iter() {
+        println!("fitness {:9.2}", c.fitness);
+        println!("Plaintext:\n{}", words.readable(&c.plaintext).0);
+        println!("Key:\n  {}\n", c.key.join("\n  "));
+    }
+    note_if_builtin(quadgrams);
+}
+
+/// Codes per letter in proportion to EN_FREQ (at least one each, largest remainders
+/// first), then the codes 0..symbols dealt out at random.
+fn generate_key<R: Rng>(symbols: usize, rng: &mut R) -> Vec<Vec<u32>> {
+    let alloc = allocation(symbols);
+    let mut codes: Vec<u32> = (0..symbols as u32).collect();
+    codes.shuffle(rng);
+    let mut it = codes.into_iter();
+    alloc.iter().map(|&k| it.by_ref().take(k).collect()).collect()
+}
+
+/// How many of `symbols` codes each letter gets
+fn allocation(symbols: usize) -> [usize; 26] {
+    let spare = symbols - 26;
+    let mut alloc = [1usize; 26];
+    let mut rem: Vec<(usize, f64)> = Vec::with_capacity(26);
+    for (i, &f) in EN_FREQ.iter().enumerate() {
+        let share = f * spare as f64;
+        alloc[i] += share.floor() as usize;
+        rem.push((i, share.fract()));
+    }
+    rem.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let given: usize = alloc.iter().sum();
+    for &(i, _) in rem.iter().take(symbols - given) {
+        alloc[i] += 1;
+    }
+    alloc
+}
+
+fn encrypt<R: Rng>(plain: &str, key: &[Vec<u32>], rng: &mut R) -> Vec<u32> {
+    plain
+        .bytes()
+        .map(|b| *key[(b - b'A') as usize].choose(rng).expect("every letter has a code"))
+        .collect()
+}
+
+/// One line per letter: "E 3 17 42 ..."
+fn format_key(key: &[Vec<u32>]) -> String {
+    key.iter()
+        .enumerate()
+        .map(|(i, codes)| {
+            let list: Vec<String> = codes.iter().map(|c| c.to_string()).collect();
+            format!("{} {}\n", (b'A' + i as u8) as char, list.join(" "))
+        })
+        .collect()
+}
+
+fn parse_key(text: &str) -> Result<HashMap<u32, char>, String> {
+    let mut key = HashMap::new();
+    for (lineno, line) in text.lines().enumerate() {
+        let mut fields = line.split_whitespace();
+        let Some(letter) = fields.next() else { continue };
+        let letter = match letter.as_bytes() {
+            [b] if b.is_ascii_alphabetic() => b.to_ascii_uppercase() as char,
+            _ => return Err(format!("line {}: expected a letter, got '{}'", lineno + 1, letter)),
+        };
+        for f in fields {
+            let code: u32 = f.parse().map_err(|_| format!("line {}: bad code '{}'", lineno + 1, f))?;
+            if key.insert(code, letter).is_some() {
+                return Err(format!("code {} is given to two letters", code));
+            }
+        }
+    }
+    Ok(key)
+}
+
+/// Digits of the widest code of a key with `symbols` codes, as `encrypt` writes them
+pub fn code_width(symbols: usize) -> usize {
+    (symbols.max(2) - 1).to_string().len()
+}
+
+/// Codes separated by anything that is not a digit; one unbroken run of digits longer
+/// than a code is read as `width`-digit codes and must split evenly into them
+pub fn parse_codes(input: &str, width: usize) -> Result<Vec<u32>, String> {
+    let tokens: Vec<&str> = input.split(|c: char| !c.is_ascii_digit()).filter(|t| !t.is_empty()).collect();
+    let tokens: Vec<&str> = match tokens.as_slice() {
+        [run] if run.len() > width => {
+            if run.len() % width != 0 {
+                return Err(format!("an unbroken run of {} digits is not a whole number of {}-digit codes", run.len(), width));
+            }
+            (0..run.len()).step_by(width).map(|i| &run[i..i + width]).collect()
+        }
+        _ => tokens,
+    };
+    tokens.iter().map(|t| t.parse().map_err(|_| format!("code '{}' is too large", t))).collect()
+}
+
+fn code_counts(codes: &[u32]) -> HashMap<u32, usize> {
+    let mut counts = HashMap::new();
+    for &c in codes.iter() {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Index of coincidence over codes instead of letters
+fn symbol_ioc(codes: &[u32]) -> f64 {
+    let n = codes.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let pairs: usize = code_counts(codes).values().map(|&c| c * (c - 1)).sum();
+    pairs as f64 / (n * (n - 1)) as f64
+}
+
+/// One nested climb from a fresh start: the letter (0..26) of each code 0..symbols, and
+/// the fitness of the decryption. With about 26 codes this is a plain substitution solve.
+pub fn climb_codes<R: Rng>(cipher: &[usize], symbols: usize, dense: &[f64], iterations: usize, rng: &mut R) -> (Vec<u8>, f64) {
+    let mut climber = Climber::new(cipher, symbols, dense, rng);
+    climber.nested(iterations, rng);
+    (climber.letter, climber.score)
+}
+
+/// Climb state: letter of every code, the decryption, its score
+struct Climber<'a> {
+    cipher: &'a [usize],
+    dense: &'a [f64],
+    /// Positions where each code occurs
+    positions: Vec<Vec<usize>>,
+    letter: Vec<u8>,
+    plain: Vec<u8>,
+    score: f64,
+    /// Scratch: window starts touched by a move, with a generation mark against duplicates
+    windows: Vec<usize>,
+    mark: Vec<u32>,
+    generation: u32,
+}
+
+impl<'a> Climber<'a> {
+    /// Start from EN_FREQ counts, the most frequent codes given to the most frequent letters
+    fn new<R: Rng>(cipher: &'a [usize], symbols: usize, dense: &'a [f64], rng: &mut R) -> Self {
+        let mut positions = vec![Vec::new(); symbols];
+        for (i, &s) in cipher.iter().enumerate() {
+            positions[s].push(i);
+        }
+        let mut by_count: Vec<usize> = (0..symbols).collect();
+        by_count.shuffle(rng);
+        by_count.sort_by_key(|&s| std::cmp::Reverse(positions[s].len()));
+
+        let alloc = allocation(symbols.max(26));
+        let mut slots: Vec<u8> = Vec::with_capacity(symbols.max(26));
+        // Deal letters round-robin, commonest letter first, so each letter gets a mix of
+        // common and rare codes
+        let mut by_freq: Vec<usize> = (0..26).collect();
+        by_freq.sort_by(|&a, &b| EN_FREQ[b].total_cmp(&EN_FREQ[a]));
+        let mut left = alloc;
+        while slots.len() < symbols {
+            for &l in by_freq.iter() {
+                if left[l] > 0 && slots.len() < symbols {
+                    left[l] -= 1;
+                    slots.push(l as u8);
+                }
+            }
+        }
+        let mut letter = vec![0u8; symbols];
+        for (&s, &l) in by_count.iter().zip(slots.iter()) {
+            letter[s] = l;
+        }
+
+        let plain: Vec<u8> = cipher.iter().map(|&s| letter[s]).collect();
+        let mut c = Climber {
+            cipher,
+            dense,
+            positions,
+            letter,
+            plain,
+            score: 0.0,
+            windows: Vec::new(),
+            mark: vec![0; cipher.len()],
+            generation: 0,
+        };
+        c.score = c.full_score();
+        c
+    }
+
+    fn full_score(&self) -> f64 {
+        self.plain.windows(4).map(|w| self.dense[quad_index([w[0], w[1], w[2], w[3]])]).sum()
+    }
+
+    /// Collect the quadgram windows that contain an occurrence of any of `codes`
+    fn touch(&mut self, codes: &[usize]) {
+        self.generation += 1;
+        self.windows.clear();
+        let last = self.cipher.len().saturating_sub(4);
+        for &s in codes {
+            for &p in self.positions[s].iter() {
+                for w in p.saturating_sub(3)..=p.min(last) {
+                    if self.mark[w] != self.generation {
+                        self.mark[w] = self.generation;
+                        self.windows.push(w);
+                    }
+                }
+            }
+        }
+    }
+
+    fn window_sum(&self) -> f64 {
+        self.windows
+            .iter()
+            .map(|&w| self.dense[quad_index([self.plain[w], self.plain[w + 1], self.plain[w + 2], self.plain[w + 3]])])
+            .sum()
+    }
+
+    fn set(&mut self, s: usize, l: u8) {
+        self.letter[s] = l;
+        for i in 0..self.positions[s].len() {
+            let p = self.positions[s][i];
+            self.plain[p] = l;
+        }
+    }
+
+    /// Give code `s` letter `l` (and `t` letter `m`) if that raises the score
+    fn try_move(&mut self, s: usize, l: u8, t: Option<(usize, u8)>) -> bool {
+        match t {
+            Some((t, _)) => self.touch(&[s, t]),
+            None => self.touch(&[s]),
+        }
+        let before = self.window_sum();
+        let old_s = self.letter[s];
+        let old_t = t.map(|(t, _)| self.letter[t]);
+        self.set(s, l);
+        if let Some((t, m)) = t {
+            self.set(t, m);
+        }
+        let delta = self.window_sum() - before;
+        if delta > 1e-9 {
+            self.score += delta;
+            true
+        } else {
+            self.set(s, old_s);
+            if let (Some((t, _)), Some(m)) = (t, old_t) {
+                self.set(t, m);
+            }
+            false
+        }
+    }
+
+    /// Inner climb: swap the letters of two codes (counts per letter unchanged) until no swap helps
+    fn inner(&mut self) {
+        let n = self.letter.len();
+        loop {
+            let mut improved = false;
+            for s in 0..n {
+                for t in (s + 1)..n {
+                    let (ls, lt) = (self.letter[s], self.letter[t]);
+                    if ls != lt && self.try_move(s, lt, Some((t, ls))) {
+                        improved = true;
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+    }
+
+    /// Outer climb: move one code to another letter, re-run the inner climb, keep if better.
+    /// Counts stay between one code and twice the EN_FREQ share plus one: left free, a
+    /// small table happily turns everything into E, S and T. Where the counts forbid the
+    /// move (always, for a plain substitution with one code per letter) the code swaps
+    /// letters with a code of the target letter instead: a kick out of the inner optimum.
+    fn nested<R: Rng>(&mut self, iterations: usize, rng: &mut R) {
+        self.inner();
+        let n = self.letter.len();
+        let expected = allocation(n.max(26));
+        for _ in 0..iterations {
+            let mut owned = [0usize; 26];
+            for &l in self.letter.iter() {
+                owned[l as usize] += 1;
+            }
+            let s = rng.gen_range(0..n);
+            let l = rng.gen_range(0..26u8);
+            let from = self.letter[s] as usize;
+            if l as usize == from {
+                continue;
+            }
+            let movable = (owned[from] > 1 || owned[l as usize] == 0) && owned[l as usize] <= 2 * expected[l as usize];
+            let partner = if movable {
+                None
+            } else {
+                let holders: Vec<usize> = (0..n).filter(|&t| self.letter[t] == l).collect();
+                match holders.as_slice() {
+                    [] => continue,
+                    h => Some(h[rng.gen_range(0..h.len())]),
+                }
+            };
+            let (saved_letter, saved_score) = (self.letter.clone(), self.score);
+            // Forced move: apply even if it lowers the score, then let the inner climb recover
+            match partner {
+                Some(t) => self.touch(&[s, t]),
+                None => self.touch(&[s]),
+            }
+            let before = self.window_sum();
+            self.set(s, l);
+            if let Some(t) = partner {
+                self.set(t, from as u8);
+            }
+            self.score += self.window_sum() - before;
+            self.inner();
+            if self.score <= saved_score {
+                for (code, &letter) in saved_letter.iter().enumerate() {
+                    if self.letter[code] != letter {
+                        self.set(code, letter);
+                    }
+                }
+                self.score = saved_score;
+            }
+        }
+    }
+}
@@ -0,0 +1,418 @@
+// identify.rs
+// Which cipher is this? Each family leaves its own fingerprint in the statistics:
+//
+//   transposition   letters merely reordered: IoC ~0.066, frequencies fit English as-is,
+//                   but bigrams are scrambled (bigram IoC near that of independent letters)
+//   plain text      as transposition, with English bigrams as well
+//   Caesar          IoC ~0.066, frequencies fit English after one shift
+//   substitution    IoC ~0.066, no shift fits, but the sorted frequency profile does;
+//                   bigram IoC stays English (pairs relabelled, not broken up) while
+//                   chi-squared against English digraphs is far off
+//   Vigenere        IoC low overall, back to ~0.066 in the columns of some period;
+//                   Kasiski repeats at multiples of that period
+//   Playfair        IoC in between, even length, no doubled letter inside a digraph,
+//                   25-letter alphabet (J is usually merged into I)
+//   XOR bytes       input is bytes (hex with some a-f), not letters, and their IoC is
+//                   above that of uniform bytes
+//   homophonic      input is decimal numbers: codes standing for letters
+//
+// The guess then picks the breaker: Caesar and Vigenere go to the Kasiski pipeline,
+// substitution to the hill-climber (Quagmire I with period 1 is a general substitution),
+// decimal codes to the homophonic breaker, XOR to a repeating-key XOR solver.
+
+use std::collections::HashMap;
+
+use crate::cli::{Cli, HomophonicArgs, IdentifyArgs, QuagmireArgs};
+use crate::report::{print_json, sorted_factors, IdentifyReport, LetterFeatures, PeriodIoc};
+use crate::segment::Segmenter;
+use crate::{
+    best_shift_for_column, clean_text, collect_distances, estimate_period, factor_frequencies, find_repeats,
+    homophonic, index_of_coincidence, letter_counts, quagmire, MIN_NGRAM, EN_FREQ,
+};
+
+/// IoC at or above this looks like a single (monoalphabetic) English alphabet
+const MONO_IOC: f64 = 0.055;
+
+/// Bigram IoC of English without spaces is ~0.0084; independent letters with English
+/// frequencies give ~0.0044 (0.066^2). Between the two: letter order was scrambled.
+const BIGRAM_IOC: f64 = 0.0062;
+
+/// IoC of uniformly random bytes; a repeating key over text leaves clearly more
+const UNIFORM_BYTE_IOC: f64 = 1.0 / 256.0;
+
+/// Key size the homophonic breaker is run with (its default), which fixes the code width
+const HOMOPHONIC_SYMBOLS: usize = 100;
+
+/// Share of printable bytes below which an XOR solution is not believed
+const PRINTABLE: f64 = 0.95;
+
+/// Commonest English digraphs, percent of all bigrams (Norvig, "English Letter Frequency
+/// Counts: Mayzner Revisited"); the rest is pooled into one bin for chi-squared
+const EN_BIGRAMS: [(&[u8; 2], f64); 50] = [
+    (b"TH", 3.56), (b"HE", 3.07), (b"IN", 2.43), (b"ER", 2.05), (b"AN", 1.99), (b"RE", 1.85), (b"ON", 1.76),
+    (b"AT", 1.49), (b"EN", 1.45), (b"ND", 1.35), (b"TI", 1.34), (b"ES", 1.34), (b"OR", 1.28), (b"TE", 1.20),
+    (b"OF", 1.17), (b"ED", 1.17), (b"IS", 1.13), (b"IT", 1.12), (b"AL", 1.09), (b"AR", 1.07), (b"ST", 1.05),
+    (b"TO", 1.04), (b"NT", 1.04), (b"NG", 0.95), (b"SE", 0.93), (b"HA", 0.93), (b"AS", 0.87), (b"OU", 0.87),
+    (b"IO", 0.83), (b"LE", 0.83), (b"VE", 0.83), (b"CO", 0.79), (b"ME", 0.79), (b"DE", 0.76), (b"HI", 0.76),
+    (b"RI", 0.73), (b"RO", 0.73), (b"IC", 0.70), (b"NE", 0.69), (b"EA", 0.69), (b"RA", 0.69), (b"CE", 0.65),
+    (b"LI", 0.62), (b"CH", 0.60), (b"LL", 0.58), (b"BE", 0.58), (b"MA", 0.57), (b"SI", 0.55), (b"OM", 0.55),
+    (b"UR", 0.54),
+];
+
+/// Entry point for `identify`; `input` is the raw text, before `clean_text`.
+pub fn run(cli: &Cli, args: &IdentifyArgs, input: &str, quadgrams: &HashMap<[u8; 4], f64>, words: &Segmenter) {
+    let report = match (decode_hex(input), decimal_codes(input)) {
+        (Some(bytes), _) => identify_bytes(&bytes),
+        (None, Some(codes)) => identify_codes(&codes),
+        (None, None) => {
+            let cipher = clean_text(input);
+            if cipher.len() < 6 {
+                eprintln!("Ciphertext too short after cleaning: length {}", cipher.len());
+                std::process::exit(1);
+            }
+            identify_letters(&cipher, args.max_key_len)
+        }
+    };
+
+    if cli.json {
+        print_json(&report);
+        return;
+    }
+    println!("Input: {} ({} symbols, {} distinct)", report.input_kind, report.length, report.alphabet_size);
+    println!("IoC: {:.4} (English ~0.066, random letters ~0.038)", report.ioc);
+    if let Some(f) = &report.letters {
+        println!("Best single shift: {} (chi-sq {:.2} per letter)", f.best_shift, f.best_shift_chi_per_letter);
+        println!("Sorted-frequency fit to English: chi-sq {:.2} per letter", f.sorted_chi_per_letter);
+        println!("Likely period: {} | top Kasiski factors: {:?}", f.period, f.top_factors.iter().map(|c| c.factor).collect::<Vec<_>>());
+        println!(
+            "Bigrams: IoC {:.4} (English ~0.0084, scrambled order ~0.0044) | chi-sq vs English digraphs {:.2} per bigram",
+            f.bigram_ioc, f.bigram_chi_per_bigram
+        );
+        println!(
+            "Doubled letters: {} ({} inside digraphs) | even length: {} | missing letters: {}",
+            f.doubled_letters,
+            f.doubled_in_pairs,
+            f.even_length,
+            if f.missing_letters.is_empty() { "none" } else { &f.missing_letters }
+        );
+    }
+    println!("\nLikely cipher: {}", report.family);
+    for r in report.reasons.iter() {
+        println!("  - {}", r);
+    }
+    if args.no_break {
+        return;
+    }
+    println!();
+
+    match report.family {
+        "caesar" => crate::break_vigenere(cli, &clean_text(input), Some(1..=1), quadgrams, words, false),
+        "vigenere" => crate::break_vigenere(cli, &clean_text(input), None, quadgrams, words, false),
+        "substitution" => {
+            let args = QuagmireArgs {
+                variant: 1,
+                keyword: None,
+                ct_keyword: None,
+                indicator: None,
+                decrypt: false,
+                period: Some(1),
+                restarts: 20,
+                iterations: 20000,
+            };
+            quagmire::run(cli, &args, &clean_text(input), quadgrams, words);
+        }
+        "homophonic" => {
+            let args = HomophonicArgs { encrypt: None, decrypt: None, symbols: HOMOPHONIC_SYMBOLS, restarts: 3, iterations: 300 };
+            homophonic::run(cli, &args, input, quadgrams, words);
+        }
+        "xor-bytes" => {
+            let bytes = decode_hex(input).expect("identified as hex");
+            let (key, plain) = break_xor(&bytes, args.max_key_len);
+            let printable = plain.iter().filter(|b| b.is_ascii_graphic() || b.is_ascii_whitespace()).count();
+            let share = printable as f64 / plain.len().max(1) as f64;
+            if share < PRINTABLE {
+                println!("Verdict: no credible solution (the best XOR key leaves {:.0}% of the bytes unprintable)", 100.0 * (1.0 - share));
+                return;
+            }
+            println!("XOR key ({} bytes): {}", key.len(), hex::encode(&key));
+            println!("Plaintext:\n{}", String::from_utf8_lossy(&plain));
+        }
+        "random-bytes" => println!("Nothing to break: the bytes look uniform, as a one-time pad or a modern cipher leaves them."),
+        "plaintext" => println!("Nothing to break: the letters and their pairs are English already."),
+        other => println!("No breaker for {} in this tool yet.", other),
+    }
+}
+
+/// Hex digits only (whitespace ignored), even count, with both a decimal digit and one
+/// of a-f: without the letters it is decimal codes, without the digits it is text
+fn decode_hex(input: &str) -> Option<Vec<u8>> {
+    let digits: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.bytes().any(|b| b.is_ascii_digit()) || !digits.bytes().any(|b| matches!(b.to_ascii_lowercase(), b'a'..=b'f')) {
+        return None;
+    }
+    hex::decode(digits).ok()
+}
+
+/// Decimal digits and whitespace only: whitespace-separated codes, or one unbroken run
+/// read as two-digit codes (as `homophonic` writes them for up to 100 codes)
+fn decimal_codes(input: &str) -> Option<Vec<u32>> {
+    if !input.chars().all(|c| c.is_ascii_digit() || c.is_whitespace()) || input.trim().is_empty() {
+        return None;
+    }
+    match homophonic::parse_codes(input, homophonic::code_width(HOMOPHONIC_SYMBOLS)) {
+        Ok(codes) => Some(codes),
+        Err(e) => {
+            eprintln!("Decimal input, but {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn identify_codes(codes: &[u32]) -> IdentifyReport {
+    let mut counts: HashMap<u32, usize> = HashMap::new();
+    for &c in codes.iter() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let n = codes.len();
+    let pairs: usize = counts.values().map(|&c| c * c.saturating_sub(1)).sum();
+    let distinct = counts.len();
+    let mut reasons = vec!["input is decimal numbers (no a-f, so not hex): codes standing for letters".to_string()];
+    reasons.push(if distinct > 26 {
+        format!("{} distinct codes, more than the 26 letters: letters own several codes each", distinct)
+    } else {
+        format!("{} distinct codes, no more than 26: maybe one per letter, which a homophonic solve covers too", distinct)
+    });
+    IdentifyReport {
+        input_kind: "decimal codes",
+        length: n,
+        alphabet_size: distinct,
+        ioc: if n < 2 { 0.0 } else { pairs as f64 / (n * (n - 1)) as f64 },
+        letters: None,
+        family: "homophonic",
+        reasons,
+    }
+}
+
+fn identify_bytes(bytes: &[u8]) -> IdentifyReport {
+    let mut counts = [0usize; 256];
+    for &b in bytes.iter() {
+        counts[b as usize] += 1;
+    }
+    let n = bytes.len();
+    let pairs: usize = counts.iter().map(|&c| c * c.saturating_sub(1)).sum();
+    let ioc = if n < 2 { 0.0 } else { pairs as f64 / (n * (n - 1)) as f64 };
+    let mut reasons = vec!["input is hex-encoded bytes, not letters".to_string()];
+    // A repeating key over text keeps some of the text's byte coincidences; a one-time pad
+    // or a modern cipher leaves the ~1/256 of uniform bytes
+    let family = if ioc >= 1.5 * UNIFORM_BYTE_IOC {
+        reasons.push(format!("byte IoC {:.4} is well above uniform bytes ({:.4}): a repeating key can do that", ioc, UNIFORM_BYTE_IOC));
+        "xor-bytes"
+    } else {
+        reasons.push(format!("byte IoC {:.4} is close to that of uniform bytes ({:.4}): nothing for the XOR solver to grip", ioc, UNIFORM_BYTE_IOC));
+        "random-bytes"
+    };
+    IdentifyReport {
+        input_kind: "hex bytes",
+        length: n,
+        alphabet_size: counts.iter().filter(|&&c| c > 0).count(),
+        ioc,
+        letters: None,
+        family,
+        reasons,
+    }
+}
+
+fn identify_letters(cipher: &str, max_key_len: usize) -> IdentifyReport {
+    let bytes = cipher.as_bytes();
+    let n = bytes.len();
+    let counts = letter_counts(bytes);
+    let ioc = index_of_coincidence(bytes);
+
+    let (best_shift, best_chi) = best_shift_for_column(bytes);
+    let best_shift_chi_per_letter = best_chi / n as f64;
+    let relabelled_chi = sorted_chi(&counts);
+    let sorted_chi_per_letter = relabelled_chi / n as f64;
+    // Real text never fits EN_FREQ exactly, so judge a shift against the best any relabelling
+    // could do (the sorted profile), with the 25 expected of a chi-squared(25) as the floor
+    let shift_fits = best_chi <= 5.0 * relabelled_chi.max(25.0);
+
+    let (period, scores) = estimate_period(cipher, max_key_len);
+    let period_ioc = scores.iter().find(|&&(p, _)| p == period).map_or(0.0, |&(_, i)| i);
+    let factors = sorted_factors(factor_frequencies(&collect_distances(&find_repeats(cipher, MIN_NGRAM))));
+
+    let (bigram_ioc, bigram_chi) = bigram_stats(bytes);
+    let bigram_chi_per_bigram = bigram_chi / n.saturating_sub(1).max(1) as f64;
+    let doubled_letters = bytes.windows(2).filter(|w| w[0] == w[1]).count();
+    let doubled_in_pairs = bytes.chunks_exact(2).filter(|p| p[0] == p[1]).count();
+    let missing_letters: String = (0..26).filter(|&i| counts[i] == 0).map(|i| (b'A' + i as u8) as char).collect();
+    let even_length = n.is_multiple_of(2);
+
+    let mut reasons = Vec::new();
+    let family = if ioc >= MONO_IOC {
+        reasons.push(format!("IoC {:.4} is English-like: one alphabet throughout", ioc));
+        if shift_fits && best_shift == 0 && bigram_ioc >= BIGRAM_IOC {
+            reasons.push("letter frequencies already match English".to_string());
+            reasons.push(format!("bigram IoC {:.4} is English too: the letter order looks untouched", bigram_ioc));
+            "plaintext"
+        } else if shift_fits && best_shift == 0 {
+            reasons.push("letter frequencies already match English: letters were only moved".to_string());
+            reasons.push(format!("bigram IoC {:.4} is near independent letters: adjacent pairs were broken up", bigram_ioc));
+            "transposition"
+        } else if shift_fits {
+            reasons.push(format!("frequencies match English after shifting by {}", best_shift));
+            "caesar"
+        } else {
+            reasons.push("no single shift matches English, but the sorted frequency profile does".to_string());
+            if bigram_ioc >= BIGRAM_IOC {
+                reasons.push(format!(
+                    "bigram IoC {:.4} is English-like but chi-sq vs English digraphs is {:.2}: pairs relabelled, not moved",
+                    bigram_ioc, bigram_chi_per_bigram
+                ));
+            } else {
+                reasons.push(format!("bigram IoC {:.4} is low as well: there may be a transposition on top", bigram_ioc));
+            }
+            "substitution"
+        }
+    } else {
+        reasons.push(format!("IoC {:.4} is below English: several alphabets, or digraphs", ioc));
+        if even_length && doubled_in_pairs == 0 && !missing_letters.is_empty() && n >= 30 {
+            reasons.push("even length and no doubled letter inside any digraph".to_string());
+            reasons.push(format!("alphabet lacks {}: Playfair uses 25 letters", missing_letters));
+            "playfair"
+        } else if period > 1 && period_ioc >= MONO_IOC {
+            reasons.push(format!("columns of period {} have English IoC {:.4}", period, period_ioc));
+            if let Some(top) = factors.first() {
+                reasons.push(format!("most common Kasiski factor: {} ({} distances)", top.factor, top.count));
+            }
+            "vigenere"
+        } else {
+            reasons.push(format!("no period up to {} restores English IoC: long key or running key", max_key_len));
+            "polyalphabetic"
+        }
+    };
+
+    IdentifyReport {
+        input_kind: "letters",
+        length: n,
+        alphabet_size: 26 - missing_letters.len(),
+        ioc,
+        letters: Some(LetterFeatures {
+            missing_letters,
+            best_shift,
+            best_shift_chi_per_letter,
+            sorted_chi_per_letter,
+            period,
+            period_ioc: scores.into_iter().map(|(period, ioc)| PeriodIoc { period, ioc }).collect(),
+            top_factors: factors.into_iter().take(5).collect(),
+            bigram_ioc,
+            bigram_chi_per_bigram,
+            doubled_letters,
+            doubled_in_pairs,
+            even_length,
+        }),
+        family,
+        reasons,
+    }
+}
+
+/// Bigram IoC over the n - 1 overlapping pairs, and chi-squared of the pair counts against
+/// `EN_BIGRAMS` (the unlisted pairs as one bin)
+fn bigram_stats(bytes: &[u8]) -> (f64, f64) {
+    let mut counts = [0usize; 26 * 26];
+    for w in bytes.windows(2) {
+        counts[(w[0] - b'A') as usize * 26 + (w[1] - b'A') as usize] += 1;
+    }
+    let n = bytes.len().saturating_sub(1);
+    if n < 2 {
+        return (0.0, 0.0);
+    }
+    let pairs: usize = counts.iter().map(|&c| c * c.saturating_sub(1)).sum();
+    let ioc = pairs as f64 / (n * (n - 1)) as f64;
+
+    let chi_bin = |observed: usize, percent: f64| {
+        let expected = percent / 100.0 * n as f64;
+        (observed as f64 - expected).powi(2) / expected
+    };
+    let mut rest = n;
+    let mut rest_percent = 100.0;
+    let mut chi = 0.0;
+    for (pair, percent) in EN_BIGRAMS.iter() {
+        let observed = counts[(pair[0] - b'A') as usize * 26 + (pair[1] - b'A') as usize];
+        rest -= observed;
+        rest_percent -= percent;
+        chi += chi_bin(observed, *percent);
+    }
+    (ioc, chi + chi_bin(rest, rest_percent))
+}
+
+/// Chi-squared of the sorted letter counts against the sorted English frequencies
+fn sorted_chi(counts: &[usize; 26]) -> f64 {
+    let n: usize = counts.iter().sum();
+    let mut obs = *counts;
+    obs.sort_unstable_by(|a, b| b.cmp(a));
+    let mut en = EN_FREQ;
+    en.sort_unstable_by(|a, b| b.total_cmp(a));
+    obs.iter()
+        .zip(en.iter())
+        .map(|(&o, &f)| {
+            let expected = f * n as f64;
+            (o as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Repeating-key XOR: key size from the normalised Hamming distance between blocks,
+/// then each key byte on its own as the byte that makes its column look most like text.
+pub fn break_xor(bytes: &[u8], max_key_len: usize) -> (Vec<u8>, Vec<u8>) {
+    let mut sizes: Vec<(usize, f64)> = (1..=max_key_len.min(bytes.len() / 2).max(1))
+        .map(|k| {
+            let blocks: Vec<&[u8]> = bytes.chunks_exact(k).collect();
+            let pairs = blocks.windows(2).take(20);
+            let (sum, cnt) = pairs.fold((0.0, 0usize), |(s, c), w| {
+                let bits: u32 = w[0].iter().zip(w[1]).map(|(a, b)| (a ^ b).count_ones()).sum();
+                (s + bits as f64 / k as f64, c + 1)
+            });
+            (k, if cnt == 0 { f64::MAX } else { sum / cnt as f64 })
+        })
+        .collect();
+    sizes.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    // Solve the most promising sizes; a multiple of the true size scores the same, so
+    // prefer the shortest key within 2% of the best
+    let mut solved: Vec<(usize, f64, Vec<u8>)> = sizes
+        .iter()
+        .take(3)
+        .map(|&(k, _)| {
+            let key: Vec<u8> = (0..k)
+                .map(|col| {
+                    let column: Vec<u8> = bytes.iter().skip(col).step_by(k).copied().collect();
+                    (0..=255u8).max_by(|&a, &b| text_score(&column, a).total_cmp(&text_score(&column, b))).unwrap_or(0)
+                })
+                .collect();
+            let plain = xor_with(bytes, &key);
+            (k, text_score(&plain, 0), key)
+        })
+        .collect();
+    solved.sort_by_key(|s| s.0);
+    let best = solved.iter().map(|s| s.1).fold(f64::MIN, f64::max);
+    let (_, _, key) = solved.into_iter().find(|s| s.1 >= best - 0.02 * best.abs()).expect("at least one size");
+    let plain = xor_with(bytes, &key);
+    (key, plain)
+}
+
+fn xor_with(bytes: &[u8], key: &[u8]) -> Vec<u8> {
+    bytes.iter().zip(key.iter().cycle()).map(|(b, k)| b ^ k).collect()
+}
+
+/// How much `bytes ^ key` looks like English text: letter frequencies, spaces, no control bytes
+fn text_score(bytes: &[u8], key: u8) -> f64 {
+    bytes
+        .iter()
+        .map(|&b| match b ^ key {
+            c if c.is_ascii_alphabetic() => EN_FREQ[(c.to_ascii_uppercase() - b'A') as usize],
+            b' ' => 0.15,
+            c if c.is_ascii_punctuation() || c.is_ascii_digit() || c == b'\n' => 0.01,
+            _ => -0.5,
+        })
+        .sum()
+}
@@ -1,14 +1,48 @@
 // break_vigenere.rs
-// Interactive Vigenere breaker: Kasiski -> chi-sq column solve -> tetragram scoring
-// Usage: cargo run --release
+// Vigenere breaker: Kasiski -> chi-sq column solve -> tetragram scoring
+// Usage: cargo run --release [-- --help]
 //
-// Paste ciphertext at the prompt and press Ctrl+D (Unix) or Ctrl+Z then Enter (Windows).
+// Without flags, paste ciphertext at the prompt and press Ctrl+D (Unix) or Ctrl+Z then Enter (Windows).
+// Scripted use: cargo run --release -- --input cipher.txt --key-len 4-12 --top 5 --scorer quadgram --json
+// Other attacks are subcommands, e.g. cargo run --release -- running-key --input cipher.txt
+// Manual solving in the terminal: cargo run --release -- tui --input cipher.txt
+// Homophonic (numeric codes): cargo run --release -- homophonic --encrypt key.txt --input plain.txt
+// ADFGVX: cargo run --release -- fractionate --square KEYWORD --key CARGO --input plain.txt
+// How much ciphertext is enough: cargo run --release -- unicity --quadgrams english_quadgrams.txt
+// Plaintext is printed split into words; --words count_1w.txt gives a better word list.
 //
 // Note: table of quadgrams here is a seed. For best accuracy on short text, replace it
 // with a comprehensive quadgram log-probability table.
 
+mod cli;
+mod confidence;
+mod crib;
+mod dict;
+mod fractionate;
+mod homophonic;
+mod identify;
+mod mic;
+mod ngrams;
+mod quagmire;
+mod report;
+mod runningkey;
+mod segment;
+mod suffix;
+mod tui;
+mod unicity;
+
 use std::collections::HashMap;
-use std::io::{self, Read, Write};
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::ops::RangeInclusive;
+
+use clap::Parser;
+
+use cli::{Cli, ColumnSolver, Command, Scorer};
+use ngrams::QUADGRAM_FLOOR;
+use segment::Segmenter;
+use confidence::Calibration;
+use report::{print_json, sorted_factors, sorted_repeats, Candidate, FactorCount, Report};
 
 const EN_FREQ: [f64; 26] = [
     0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094,
@@ -17,125 +51,292 @@ const EN_FREQ: [f64; 26] = [
     0.01974, 0.00074,
 ];
 
+/// Shortest repeated n-gram used for Kasiski examination
+const MIN_NGRAM: usize = 3;
+/// Longest maximal repeats written out in the report, and positions shown per repeat
+const REPORTED_REPEATS: usize = 20;
+const REPORTED_POSITIONS: usize = 20;
+
 fn main() {
-    // 1) Interactive input
-    print!("Input the ciphertext (paste then press Ctrl+D on Unix or Ctrl+Z then Enter on Windows):\n");
-    io::stdout().flush().expect("flush failed");
+    let cli = Cli::parse();
+    // Prompts only make sense for a human pasting into a terminal
+    let interactive = cli.reads_stdin() && !cli.json && io::stdin().is_terminal();
+
+    // 1) Input: file, or stdin (the unicity calculator needs none)
+    let input = if matches!(cli.command, Some(Command::Unicity(_))) {
+        String::new()
+    } else {
+        match read_input(&cli, interactive) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Failed to read ciphertext: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let quadgrams = match &cli.quadgrams {
+        Some(path) => match ngrams::load_quadgrams(path) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("Failed to load quadgrams from {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => quadgram_table(),
+    };
 
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input).expect("Failed to read stdin");
+    let words = match &cli.words {
+        Some(path) => match Segmenter::load(path) {
+            Ok(w) => w,
+            Err(e) => {
+                eprintln!("Failed to load words from {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => Segmenter::builtin(),
+    };
+
+    // These look at what cleaning throws away (digits, hex bytes)
+    match &cli.command {
+        Some(Command::Identify(args)) => return identify::run(&cli, args, &input, &quadgrams, &words),
+        Some(Command::Homophonic(args)) => return homophonic::run(&cli, args, &input, &quadgrams, &words),
+        Some(Command::Fractionate(args)) => return fractionate::run(&cli, args, &input, &quadgrams, &words),
+        Some(Command::Unicity(args)) => return unicity::run(&cli, args, &quadgrams),
+        _ => {}
+    }
 
     let cipher = clean_text(&input);
     if cipher.len() < 6 {
         eprintln!("Ciphertext too short after cleaning: length {}", cipher.len());
-        return;
+        std::process::exit(1);
+    }
+
+    match &cli.command {
+        None => run_break(&cli, &cipher, &quadgrams, &words, interactive),
+        Some(Command::RunningKey(args)) => runningkey::run(&cli, args, &cipher, &quadgrams, &words),
+        Some(Command::Quagmire(args)) => quagmire::run(&cli, args, &cipher, &quadgrams, &words),
+        Some(Command::Crib(args)) => crib::run(&cli, args, &cipher, &quadgrams, &words),
+        Some(Command::Dict(args)) => dict::run(&cli, args, &cipher, &quadgrams, &words),
+        Some(Command::Tui(args)) => tui::run(&cli, args, &cipher, &quadgrams),
+        Some(Command::Identify(_)) | Some(Command::Homophonic(_)) | Some(Command::Fractionate(_)) | Some(Command::Unicity(_)) => {
+            unreachable!("handled above")
+        }
     }
-    println!("\nCiphertext length (letters only): {}\n", cipher.len());
+}
+
+/// Default mode: Kasiski -> chi-sq column solve -> n-gram ranking
+fn run_break(cli: &Cli, cipher: &str, quadgrams: &HashMap<[u8;4], f64>, words: &Segmenter, interactive: bool) {
+    // Optional: force key length (flag, or prompt when interactive)
+    let forced = match cli.key_len.clone() {
+        Some(r) => Some(r),
+        None if interactive => {
+            println!("\nCiphertext length (letters only): {}\n", cipher.len());
+            prompt_key_len()
+        }
+        None => None,
+    };
+    break_vigenere(cli, cipher, forced, quadgrams, words, interactive);
+}
 
-    // Optional: force key length
+/// Analyse with the given key lengths (None: Kasiski's choice) and print the report
+fn break_vigenere(
+    cli: &Cli,
+    cipher: &str,
+    forced: Option<RangeInclusive<usize>>,
+    quadgrams: &HashMap<[u8;4], f64>,
+    words: &Segmenter,
+    interactive: bool,
+) {
+    // Optional English sample for calibrating the fitness numbers
+    let reference = cli.reference.as_ref().map(|path| match fs::read_to_string(path) {
+        Ok(t) => clean_text(&t),
+        Err(e) => {
+            eprintln!("Failed to read reference text {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    });
+
+    let calibration = confidence::calibrate(quadgrams, reference.as_deref());
+    let report = analyse(cipher, forced, cli, quadgrams, words, &calibration);
+
+    if cli.json {
+        print_json(&report);
+    } else {
+        print_report(&report, cli.scorer, interactive);
+    }
+}
+
+/// Read the whole ciphertext from the --input file or stdin
+fn read_input(cli: &Cli, interactive: bool) -> io::Result<String> {
+    match &cli.input {
+        Some(path) if !cli.reads_stdin() => fs::read_to_string(path),
+        _ => {
+            if interactive {
+                println!("Input the ciphertext (paste then press Ctrl+D on Unix or Ctrl+Z then Enter on Windows):");
+                io::stdout().flush()?;
+            }
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+/// Ask for a key length; empty or invalid answer means "let the breaker choose"
+fn prompt_key_len() -> Option<RangeInclusive<usize>> {
     print!("(Optional) Force key length? Press Enter to skip, or type a number: ");
     io::stdout().flush().unwrap();
     let mut buf = String::new();
     io::stdin().read_line(&mut buf).unwrap();
-    let forced_k: Option<usize> = buf.trim().parse::<usize>().ok();
+    match buf.trim().parse::<usize>() {
+        Ok(k) if k > 0 => Some(k..=k),
+        _ => None,
+    }
+}
 
+/// Run the whole pipeline: repeats -> distances -> factors -> per-length solve -> ranking
+fn analyse(
+    cipher: &str,
+    forced: Option<RangeInclusive<usize>>,
+    cli: &Cli,
+    quadgrams: &HashMap<[u8;4], f64>,
+    words: &Segmenter,
+    calibration: &Calibration,
+) -> Report {
+    let (scorer, solver) = (cli.scorer, cli.solver);
     // 2) Kasiski-ish repeats
-    let min_ngram = 3usize;
-    let repeats = find_repeats(&cipher, min_ngram);
+    let repeats = find_repeats(cipher, MIN_NGRAM);
 
-    if repeats.is_empty() {
-        println!("No repeated {}-grams found. Try smaller n or provide more ciphertext.", min_ngram);
+    // 3) distances & factor frequencies
+    let distances = collect_distances(&repeats);
+    let factors = sorted_factors(factor_frequencies(&distances));
+
+    // 4) Candidate list
+    let candidates: Vec<usize> = match forced {
+        Some(range) => range.collect(),
+        None => kasiski_key_lengths(&factors, 12),
+    };
+
+    // 5) Solve each candidate (chi-sq or mutual IoC), then score plain with the chosen scorer
+    let tetragrams = tetragram_table();
+    let table = match scorer {
+        Scorer::Quadgram | Scorer::Words => quadgrams,
+        Scorer::Tetragram | Scorer::Chi => &tetragrams,
+    };
+    let mut results: Vec<Candidate> = Vec::new();
+    for &klen in candidates.iter() {
+        let (key, chi_squared, plaintext) = try_key_length(cipher, klen, solver);
+        let fitness = match scorer {
+            Scorer::Quadgram | Scorer::Words => quadgram_score(&plaintext, table),
+            Scorer::Tetragram | Scorer::Chi => tetragram_score(&plaintext, table),
+        };
+        // Segmenting is costly on long texts: only the words scorer needs it for every candidate
+        let (segmented, word_coverage) = if scorer == Scorer::Words { words.readable(&plaintext) } else { (String::new(), 0.0) };
+        let confidence = confidence::assess(calibration, &plaintext, quadgrams, chi_squared, klen);
+        results.push(Candidate { key_length: klen, key, chi_squared, fitness, word_coverage, confidence, plaintext, segmented });
+    }
+
+    let by_fitness = |a: &Candidate, b: &Candidate| b.fitness.partial_cmp(&a.fitness).unwrap_or(std::cmp::Ordering::Equal);
+    let by_chi = |a: &Candidate, b: &Candidate| a.chi_squared.partial_cmp(&b.chi_squared).unwrap_or(std::cmp::Ordering::Equal);
+    match scorer {
+        // sort primarily by fitness descending, tie-break by chi-score ascending
+        Scorer::Tetragram | Scorer::Quadgram => results.sort_by(|a, b| by_fitness(a, b).then_with(|| by_chi(a, b))),
+        Scorer::Chi => results.sort_by(|a, b| by_chi(a, b).then_with(|| by_fitness(a, b))),
+        Scorer::Words => results.sort_by(|a, b| b.word_coverage.total_cmp(&a.word_coverage).then_with(|| by_fitness(a, b))),
+    }
+    results.truncate(cli.top);
+    if scorer != Scorer::Words {
+        for c in results.iter_mut() {
+            (c.segmented, c.word_coverage) = words.readable(&c.plaintext);
+        }
+    }
+    let verdict = match results.iter().find(|c| c.confidence.verdict == "english") {
+        Some(c) => format!("credible solution: key {}", c.key),
+        None => "no credible solution".to_string(),
+    };
+
+    Report {
+        ciphertext_length: cipher.len(),
+        scorer: scorer.name(),
+        column_solver: solver.name(),
+        repeat_count: repeats.count,
+        repeats: sorted_repeats(&repeats, cipher),
+        distances,
+        factor_counts: factors,
+        key_lengths_tried: candidates,
+        candidates: results,
+        calibration: calibration.clone(),
+        verdict,
+    }
+}
+
+/// Human-readable report (the original demo output)
+fn print_report(report: &Report, scorer: Scorer, interactive: bool) {
+    if !interactive {
+        println!("Ciphertext length (letters only): {}\n", report.ciphertext_length);
+    }
+
+    if report.repeats.is_empty() {
+        println!("No repeated sequences of length >= {} found. Provide more ciphertext.", MIN_NGRAM);
     } else {
-        println!("Found repeated {}-grams (showing some):", min_ngram);
-        let mut shown = 0;
-        for (ng, pos) in repeats.iter() {
-            if pos.len() > 1 && shown < 20 {
-                println!("  {} -> positions {:?}", ng, pos);
-                shown += 1;
-            }
+        println!("Found {} maximal repeated sequences of length >= {} (showing longest):", report.repeat_count, MIN_NGRAM);
+        for r in report.repeats.iter() {
+            let more = if r.occurrences > r.positions.len() { format!(" ... ({} in all)", r.occurrences) } else { String::new() };
+            println!("  {} ({}) -> positions {:?}{}", r.ngram, r.length, r.positions, more);
         }
         println!();
     }
 
-    // 3) distances & factor frequencies
-    let distances = collect_distances(&repeats);
-    if !distances.is_empty() {
+    if !report.distances.is_empty() {
         println!("Some distances between repeated sequences (showing up to 40):");
-        for d in distances.iter().take(40) {
+        for d in report.distances.iter().take(40) {
             print!("{} ", d);
         }
         println!("\n");
 
-        let factor_counts = factor_frequencies(&distances);
         println!("Most common factors (candidate key lengths):");
-        let mut factors: Vec<_> = factor_counts.into_iter().collect();
-        factors.sort_by(|a, b| b.1.cmp(&a.1));
-        for (f, cnt) in factors.iter().take(12) {
-            println!("  {:2} -> count {}", f, cnt);
+        for fc in report.factor_counts.iter().take(12) {
+            println!("  {:2} -> count {}", fc.factor, fc.count);
         }
         println!();
     } else {
         println!("No distances found from repeats.\n");
     }
 
-    // 4) Candidate list
-    let mut candidates: Vec<usize> = Vec::new();
-    if let Some(k) = forced_k {
-        if k > 0 {
-            candidates.push(k);
-        }
-    } else {
-        // gather from factor counts (2..=40)
-        let mut factor_counts = factor_frequencies(&distances);
-        let mut factors: Vec<_> = factor_counts.into_iter().collect();
-        factors.sort_by(|a, b| b.1.cmp(&a.1));
-        for (f, _cnt) in factors.iter().take(8) {
-            if *f > 1 && *f <= 60 {
-                candidates.push(*f);
-            }
-        }
-        // fill with 1..12
-        for k in 1..13 {
-            if !candidates.contains(&k) {
-                candidates.push(k);
-            }
-        }
-    }
-    candidates.sort();
-    candidates.dedup();
-
-    println!("Trying candidate key lengths: {:?}\n", candidates);
-
-    // 5) Solve each candidate with chi-sq, then score plain with quadgrams
-    let quad_table = tetragram_table(); // actually quadgrams
-    let mut results: Vec<(usize, String, f64, f64, String)> = Vec::new();
-    // (klen, key, chi_score (lower better), fitness (higher better), plaintext)
-
-    for &klen in candidates.iter() {
-        if klen == 0 { continue; }
-        let (key, chi_score, plain) = try_key_length(&cipher, klen);
-        let fitness = tetragram_score(&plain, &quad_table);
-        results.push((klen, key, chi_score, fitness, plain));
-    }
-
-    // sort primarily by fitness descending, tie-break by chi-score ascending
-    results.sort_by(|a, b| {
-        b.3.partial_cmp(&a.3).unwrap_or(std::cmp::Ordering::Equal)
-            .then_with(|| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal))
-    });
+    println!("Trying candidate key lengths: {:?}\n", report.key_lengths_tried);
 
     // print top results
-    println!("Top candidate decryptions (sorted by tetragram fitness):\n");
-    for (klen, key, chi, fit, plain) in results.iter().take(10) {
-        println!("Key len {:2} | chi-sq {:8.2} | fitness {:8.2} | key: {}", klen, chi, fit, key);
-        println!("Plaintext:\n{}\n", plain);
+    match scorer {
+        Scorer::Chi => println!("Top candidate decryptions (sorted by chi-squared):\n"),
+        Scorer::Words => println!("Top candidate decryptions (sorted by word coverage):\n"),
+        _ => println!("Top candidate decryptions (sorted by {} fitness):\n", scorer.name()),
     }
+    for c in report.candidates.iter() {
+        println!(
+            "Key len {:2} | chi-sq {:8.2} | fitness {:8.2} | words {:3.0}% | key: {}",
+            c.key_length, c.chi_squared, c.fitness, 100.0 * c.word_coverage, c.key
+        );
+        let k = &c.confidence;
+        println!(
+            "  confidence: {} (P(English) {:.3}) | chi-sq p {:.3} | {:.2}/quadgram, {:+.1} sd vs random, {:+.1} sd vs English",
+            k.verdict, k.english_probability, k.chi_squared_p_value, k.fitness_per_quadgram, k.z_vs_random, k.z_vs_english
+        );
+        println!("Plaintext:\n{}\n", c.segmented);
+    }
+
+    let cal = &report.calibration;
+    println!(
+        "Per-quadgram score: random letters {:.2} (sd {:.2}), English {:.2} (sd {:.2}, from {})",
+        cal.random.mean, cal.random.sd, cal.english.mean, cal.english.sd, cal.english_source
+    );
+    println!("Verdict: {}\n", report.verdict);
 
-    if results.is_empty() {
+    if report.candidates.is_empty() {
         println!("No candidate decryptions generated. Try longer ciphertext or different parameters.");
     } else {
-        println!("If the correct plaintext isn't visible, try forcing a key length when prompted,");
-        println!("or replace the tetragram table with a more complete quadgram frequency table.");
+        println!("If the correct plaintext isn't visible, try forcing a key length (--key-len),");
+        println!("or switch to the larger table with --scorer quadgram.");
     }
 }
 
@@ -147,36 +348,18 @@ fn clean_text(s: &str) -> String {
         .collect()
 }
 
-/// Find repeated n-grams of lengths n_min..=max_n
-fn find_repeats(text: &str, n_min: usize) -> HashMap<String, Vec<usize>> {
-    let mut map: HashMap<String, Vec<usize>> = HashMap::new();
-    let max_n = 6usize;
-    let len = text.len();
-    for n in n_min..=max_n {
-        if n > len { break; }
-        for i in 0..=(len - n) {
-            let ng = text[i..i + n].to_string();
-            map.entry(ng).or_default().push(i);
-        }
-    }
-    map.retain(|_k, v| v.len() > 1);
-    map
+/// Maximal repeated substrings of length >= n_min (no upper bound) and the Kasiski
+/// distances between them, from one suffix array + LCP scan.
+fn find_repeats(text: &str, n_min: usize) -> suffix::Repeats {
+    suffix::repeats(text.as_bytes(), n_min, REPORTED_REPEATS, REPORTED_POSITIONS)
 }
 
-/// Collect pairwise distances between repeated occurrences
-fn collect_distances(repeats: &HashMap<String, Vec<usize>>) -> Vec<usize> {
-    let mut distances = Vec::new();
-    for (_ng, pos) in repeats.iter() {
-        for i in 0..pos.len() {
-            for j in (i + 1)..pos.len() {
-                let dist = pos[j] - pos[i];
-                if dist > 0 {
-                    distances.push(dist);
-                }
-            }
-        }
-    }
-    distances.sort();
+/// Kasiski distances, sorted: one per pair of neighbouring suffixes that share a repeat,
+/// so O(n) in total. Every distance between two occurrences of a repeat is a sum of
+/// these, so they carry the same factors.
+fn collect_distances(repeats: &suffix::Repeats) -> Vec<usize> {
+    let mut distances = repeats.distances.clone();
+    distances.sort_unstable();
     distances
 }
 
@@ -193,55 +376,99 @@ fn factor_frequencies(distances: &[usize]) -> HashMap<usize, usize> {
     counts
 }
 
-/// For given key length, compute best shift per column via chi-sq and return key, total chi score, plaintext
-fn try_key_length(cipher: &str, klen: usize) -> (String, f64, String) {
-    let mut key = String::new();
-    let mut total_score = 0.0;
-    let bytes = cipher.as_bytes();
-    for col in 0..klen {
-        let mut col_letters: Vec<u8> = Vec::new();
-        let mut i = col;
-        while i < bytes.len() {
-            col_letters.push(bytes[i]);
-            i += klen;
+/// Candidate key lengths: the 8 most common Kasiski factors, then every length 1..=fill_to
+fn kasiski_key_lengths(factors: &[FactorCount], fill_to: usize) -> Vec<usize> {
+    let mut candidates: Vec<usize> = Vec::new();
+    // gather from factor counts (2..=60)
+    for fc in factors.iter().take(8) {
+        if fc.factor > 1 && fc.factor <= 60 {
+            candidates.push(fc.factor);
         }
-        let (best_shift, best_score) = best_shift_for_column(&col_letters);
-        let key_char = ((best_shift as u8) + b'A') as char;
-        key.push(key_char);
-        total_score += best_score;
     }
-    let plain = vigenere_decrypt(cipher, &key);
-    (key, total_score, plain)
+    // fill with 1..=fill_to
+    for k in 1..=fill_to {
+        if !candidates.contains(&k) {
+            candidates.push(k);
+        }
+    }
+    candidates.sort();
+    candidates.dedup();
+    candidates
 }
 
-/// Chi-squared best shift for a column
-fn best_shift_for_column(col: &Vec<u8>) -> (usize, f64) {
+/// Index of coincidence of uppercase letters (English ~0.066, random ~0.038)
+fn index_of_coincidence(letters: &[u8]) -> f64 {
     let mut counts = [0usize; 26];
-    for &b in col.iter() {
-        if b'A' <= b && b <= b'Z' {
+    for &b in letters.iter() {
+        if b.is_ascii_uppercase() {
             counts[(b - b'A') as usize] += 1;
         }
     }
     let n: usize = counts.iter().sum();
-    if n == 0 { return (0usize, std::f64::INFINITY); }
+    if n < 2 { return 0.0; }
+    let pairs: usize = counts.iter().map(|&c| c * c.saturating_sub(1)).sum();
+    pairs as f64 / (n * (n - 1)) as f64
+}
+
+/// Average IoC of the columns when the text is split with the given period
+fn periodic_ioc(cipher: &str, period: usize) -> f64 {
+    let bytes = cipher.as_bytes();
+    let total: f64 = (0..period)
+        .map(|col| {
+            let column: Vec<u8> = bytes.iter().skip(col).step_by(period).copied().collect();
+            index_of_coincidence(&column)
+        })
+        .sum();
+    total / period as f64
+}
+
+/// Period from Kasiski candidates ranked by periodic IoC. Multiples of the true period
+/// score as well as the period itself, so take the smallest length within 10% of the best.
+fn estimate_period(cipher: &str, max_period: usize) -> (usize, Vec<(usize, f64)>) {
+    let distances = collect_distances(&find_repeats(cipher, MIN_NGRAM));
+    let factors = sorted_factors(factor_frequencies(&distances));
+    let scores: Vec<(usize, f64)> = kasiski_key_lengths(&factors, max_period)
+        .into_iter()
+        .filter(|&p| p <= max_period && cipher.len() / p >= 2)
+        .map(|p| (p, periodic_ioc(cipher, p)))
+        .collect();
+    let best = scores.iter().map(|&(_, ioc)| ioc).fold(0.0, f64::max);
+    let period = scores.iter().find(|&&(_, ioc)| ioc >= 0.9 * best).map_or(1, |&(p, _)| p);
+    (period, scores)
+}
+
+/// For given key length, solve every column (chi-sq each on its own, or mutual IoC
+/// between columns) and return key, total chi score, plaintext
+fn try_key_length(cipher: &str, klen: usize, solver: ColumnSolver) -> (String, f64, String) {
+    let bytes = cipher.as_bytes();
+    let columns: Vec<Vec<u8>> = (0..klen)
+        .map(|col| bytes.iter().skip(col).step_by(klen).copied().collect())
+        .collect();
+    let shifts: Vec<usize> = match solver {
+        ColumnSolver::Chi => columns.iter().map(|c| best_shift_for_column(c).0).collect(),
+        ColumnSolver::Mic => mic::solve_shifts(&columns),
+    };
+    let key: String = shifts.iter().map(|&s| ((s as u8) + b'A') as char).collect();
+    let total_score: f64 = columns
+        .iter()
+        .zip(shifts.iter())
+        .map(|(c, &s)| chi_squared_for_shift(&letter_counts(c), s))
+        .sum();
+    let plain = vigenere_decrypt(cipher, &key);
+    (key, total_score, plain)
+}
+
+/// Chi-squared best shift for a column
+fn best_shift_for_column(col: &[u8]) -> (usize, f64) {
+    let counts = letter_counts(col);
+    let n: usize = counts.iter().sum();
+    if n == 0 { return (0usize, f64::INFINITY); }
 
     let mut best_shift = 0usize;
-    let mut best_score = std::f64::INFINITY;
+    let mut best_score = f64::INFINITY;
 
     for shift in 0..26 {
-        let mut obs = [0f64; 26];
-        for i in 0..26 {
-            let plain_idx = (26 + i as isize - shift as isize) as usize % 26;
-            obs[plain_idx] += counts[i] as f64;
-        }
-        let mut chi = 0.0f64;
-        for i in 0..26 {
-            let expected = EN_FREQ[i] * (n as f64);
-            if expected > 0.0 {
-                let diff = obs[i] - expected;
-                chi += diff * diff / expected;
-            }
-        }
+        let chi = chi_squared_for_shift(&counts, shift);
         if chi < best_score {
             best_score = chi;
             best_shift = shift;
@@ -250,6 +477,54 @@ fn best_shift_for_column(col: &Vec<u8>) -> (usize, f64) {
     (best_shift, best_score)
 }
 
+/// Count of each uppercase letter
+fn letter_counts(col: &[u8]) -> [usize; 26] {
+    let mut counts = [0usize; 26];
+    for &b in col.iter() {
+        if b.is_ascii_uppercase() {
+            counts[(b - b'A') as usize] += 1;
+        }
+    }
+    counts
+}
+
+/// Chi-squared against English of a column's counts decrypted with one shift
+fn chi_squared_for_shift(counts: &[usize; 26], shift: usize) -> f64 {
+    let n: usize = counts.iter().sum();
+    let mut obs = [0f64; 26];
+    for (i, &cnt) in counts.iter().enumerate() {
+        let plain_idx = (26 + i as isize - shift as isize) as usize % 26;
+        obs[plain_idx] += cnt as f64;
+    }
+    let mut chi = 0.0f64;
+    for i in 0..26 {
+        let expected = EN_FREQ[i] * (n as f64);
+        if expected > 0.0 {
+            let diff = obs[i] - expected;
+            chi += diff * diff / expected;
+        }
+    }
+    chi
+}
+
+/// Vigenere encrypt uppercase plaintext with uppercase key
+fn vigenere_encrypt(plain: &str, key: &str) -> String {
+    let mut out = String::with_capacity(plain.len());
+    let kb = key.as_bytes();
+    let klen = kb.len();
+    if klen == 0 { return String::new(); }
+
+    for (i, &pb) in plain.as_bytes().iter().enumerate() {
+        if pb.is_ascii_uppercase() {
+            let shift = kb[i % klen] - b'A';
+            out.push((b'A' + (pb - b'A' + shift) % 26) as char);
+        } else {
+            out.push(pb as char);
+        }
+    }
+    out
+}
+
 /// Vigenere decrypt uppercase ciphertext with uppercase key
 fn vigenere_decrypt(cipher: &str, key: &str) -> String {
     let mut out = String::with_capacity(cipher.len());
@@ -258,7 +533,7 @@ fn vigenere_decrypt(cipher: &str, key: &str) -> String {
     if klen == 0 { return String::new(); }
 
     for (i, &cb) in cipher.as_bytes().iter().enumerate() {
-        if cb >= b'A' && cb <= b'Z' {
+        if cb.is_ascii_uppercase() {
             let shift = (kb[i % klen] - b'A') as i32;
             // cb is already u8 (not a reference), so don't deref it
             let p = ((cb - b'A') as i32 - shift + 26) % 26;
@@ -305,12 +580,12 @@ fn tetragram_table() -> HashMap<[u8;4], f64> {
 /// Compute tetragram/quadgram score (higher = better). Use floor for unseen grams.
 fn tetragram_score(text: &str, table: &HashMap<[u8;4], f64>) -> f64 {
     let bytes = text.as_bytes();
-    if bytes.len() < 4 { return std::f64::NEG_INFINITY; }
+    if bytes.len() < 4 { return f64::NEG_INFINITY; }
     let floor = -8.0; // penalty for unseen tetragrams (tweak as needed)
     let mut s = 0.0;
     for i in 0..=bytes.len()-4 {
         let slice = &bytes[i..i+4];
-        if slice.iter().all(|&b| b'A' <= b && b <= b'Z') {
+        if slice.iter().all(|b| b.is_ascii_uppercase()) {
             let mut key = [0u8;4];
             key.copy_from_slice(slice);
             s += *table.get(&key).unwrap_or(&floor);
@@ -321,3 +596,65 @@ fn tetragram_score(text: &str, table: &HashMap<[u8;4], f64>) -> f64 {
     }
     s
 }
+
+/// English quadgram (4-gram) log10 probabilities, A–Z only.
+/// Compact subset derived from Practical Cryptography data (same table as w02_breakVigenereQuad_demo).
+/// For best accuracy on short texts, load a full table from file.
+fn quadgram_table() -> HashMap<[u8;4], f64> {
+    let mut t = HashMap::new();
+
+    // (quadgram, log10(probability)) — NO spaces, UPPERCASE
+    let entries: &[(&str, f64)] = &[
+        ("TION",-3.0009),("NTHE",-3.1082),("THER",-3.1123),("THAT",-3.1876),
+        ("OFTH",-3.2207),("FTHE",-3.2405),("INTH",-3.2588),("ATIO",-3.2992),
+        ("HERE",-3.3065),("ETHE",-3.3191),("MENT",-3.3349),("TAND",-3.3406),
+        ("IONS",-3.3654),("RTHE",-3.3826),("THES",-3.3927),("EAND",-3.6369),
+        ("THEN",-3.4824),("THEM",-3.4897),("THIS",-3.4319),("WITH",-3.4962),
+        ("TTHE",-3.5996),("NDTH",-3.5865),("FROM",-3.6172),("EVER",-3.6215),
+        ("THIN",-3.6676),("OULD",-3.6104),("INGT",-3.6333),("HAVE",-3.5827),
+        ("RETH",-3.4683),("FORE",-3.6722),("WERE",-3.6553),("EENT",-3.7100),
+        ("ANDE",-3.5598),("EDTH",-3.4470),("ERTH",-3.5687),("SION",-3.7165),
+        ("HING",-3.7300),("TENT",-3.4938),("THED",-3.6765),("GTHE",-3.7420),
+        ("NTER",-3.7600),("RING",-3.7705),("HEAR",-3.5551),("THEI",-3.5580),
+        ("THEY",-3.5642),("THRE",-3.5951),("SAND",-3.5183),("ALLT",-3.5015),
+        ("NGTH",-3.5486),("ENTS",-3.7200),("NING",-3.7350),("TIVE",-3.7600),
+        ("RATI",-3.7700),("OVER",-3.7900),("ERES",-3.5892),("STHE",-3.5381),
+    ];
+
+    for &(s, v) in entries {
+        let mut key = [0u8; 4];
+        key.copy_from_slice(s.as_bytes());
+        t.insert(key, v);
+    }
+    t
+}
+
+/// Quadgram score (higher = more English-like).
+/// Uses a strong floor for unseen grams; letters-only windows.
+fn quadgram_score(text: &str, table: &HashMap<[u8;4], f64>) -> f64 {
+    quadgram_score_bounded(text, table, f64::NEG_INFINITY).unwrap_or(f64::NEG_INFINITY)
+}
+
+/// `quadgram_score`, giving up (None) as soon as the running sum falls below `bound`.
+/// Every term is <= 0, so the running sum is an upper bound on the final score.
+fn quadgram_score_bounded(text: &str, table: &HashMap<[u8;4], f64>, bound: f64) -> Option<f64> {
+    let bytes = text.as_bytes();
+    if bytes.len() < 4 { return None; }
+
+    // Strong penalty (QUADGRAM_FLOOR) helps on short texts
+    let mut s = 0.0;
+
+    for w in bytes.windows(4) {
+        if w.iter().all(|b| b.is_ascii_uppercase()) {
+            let mut key = [0u8; 4];
+            key.copy_from_slice(w);
+            s += table.get(&key).copied().unwrap_or(QUADGRAM_FLOOR);
+        } else {
+            s += QUADGRAM_FLOOR;
+        }
+        if s < bound {
+            return None;
+        }
+    }
+    Some(s)
+}
@@ -0,0 +1,114 @@
+// mic.rs
+// Mutual index of coincidence (Friedman): the chance that a letter drawn from column i
+// equals one drawn from column j,
+//
+//   MIC(i, j) = sum_x f_i(x) f_j(x) / (n_i n_j)
+//
+// is ~0.066 when both columns were enciphered with the same shift and ~0.038 otherwise.
+// Shifting column j back by s and looking for the peak therefore gives k_j - k_i
+// directly, from *all* letters of both columns, without comparing either to English.
+// Once every column is expressed relative to column 0, only k_0 is unknown, and its
+// 26 values are ranked by chi-squared over the whole text rather than one short column.
+//
+// Columns are compared with each other, not with English, so the method does not care
+// whether the plaintext has English letter frequencies until the very last step. That is
+// where it helps: plaintext in another language, or already substituted, still gives the
+// right shifts between columns, so the key comes out right up to one common shift that a
+// crib or a glance at the output fixes. For ordinary English, prefer the per-column
+// chi-squared solve: it uses the English frequencies for every column, and a column of a
+// dozen letters says more about its shift against English than against another column of
+// a dozen letters.
+
+use crate::{chi_squared_for_shift, letter_counts};
+
+/// Key shifts (0..26) for the given columns via mutual IoC.
+pub fn solve_shifts(columns: &[Vec<u8>]) -> Vec<usize> {
+    let k = columns.len();
+    let counts: Vec<[usize; 26]> = columns.iter().map(|c| letter_counts(c)).collect();
+    if k <= 1 {
+        return counts.iter().map(|c| best_k0(&[*c], &[0]).0).collect();
+    }
+
+    // mic[i][j][s]: MIC of column i with column j shifted back by s
+    let mut mic = vec![vec![[0.0f64; 26]; k]; k];
+    for i in 0..k {
+        for j in (0..k).filter(|&j| j != i) {
+            for (s, m) in mic[i][j].iter_mut().enumerate() {
+                *m = mutual_ioc(&counts[i], &counts[j], s);
+            }
+        }
+    }
+
+    // Short columns make single peaks unreliable: build a spanning tree from every root,
+    // polish each against all pairs, and keep the one whose best k_0 fits English best.
+    (0..k)
+        .map(|root| {
+            let rel = polish(&mic, spanning_tree(&mic, root));
+            let (k0, chi) = best_k0(&counts, &rel);
+            (rel.iter().map(|&r| (k0 + r) % 26).collect::<Vec<usize>>(), chi)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .expect("at least two columns")
+        .0
+}
+
+/// Relative shifts from the most confident pairs first (Prim's maximum spanning tree)
+fn spanning_tree(mic: &[Vec<[f64; 26]>], root: usize) -> Vec<usize> {
+    let k = mic.len();
+    let mut rel: Vec<Option<usize>> = vec![None; k];
+    rel[root] = Some(0);
+    for _ in 1..k {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for (i, ri) in rel.iter().enumerate().filter_map(|(i, r)| r.map(|r| (i, r))) {
+            for j in (0..k).filter(|&j| rel[j].is_none()) {
+                let (s, v) = (0..26).map(|s| (s, mic[i][j][s])).fold((0, f64::MIN), |b, x| if x.1 > b.1 { x } else { b });
+                if best.is_none_or(|b| v > b.2) {
+                    best = Some((j, (ri + s) % 26, v));
+                }
+            }
+        }
+        let (j, r, _) = best.expect("an unplaced column remains");
+        rel[j] = Some(r);
+    }
+    rel.into_iter().map(|r| r.expect("all columns placed")).collect()
+}
+
+/// Re-place each column against all the others until nothing moves
+fn polish(mic: &[Vec<[f64; 26]>], mut rel: Vec<usize>) -> Vec<usize> {
+    let k = mic.len();
+    for _ in 0..k {
+        let mut moved = false;
+        for j in 0..k {
+            let score = |r: usize| -> f64 { (0..k).filter(|&i| i != j).map(|i| mic[i][j][(r + 26 - rel[i]) % 26]).sum() };
+            let r = (0..26).fold(rel[j], |b, r| if score(r) > score(b) { r } else { b });
+            if r != rel[j] {
+                rel[j] = r;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    rel
+}
+
+/// MIC of `a` with `b` shifted back by `s` (letter x in a against x + s in b)
+fn mutual_ioc(a: &[usize; 26], b: &[usize; 26], s: usize) -> f64 {
+    let (na, nb): (usize, usize) = (a.iter().sum(), b.iter().sum());
+    if na == 0 || nb == 0 {
+        return 0.0;
+    }
+    let sum: usize = (0..26).map(|x| a[x] * b[(x + s) % 26]).sum();
+    sum as f64 / (na * nb) as f64
+}
+
+/// The one unknown letter: k_0 with the lowest total chi-squared over all columns
+fn best_k0(counts: &[[usize; 26]], rel: &[usize]) -> (usize, f64) {
+    (0..26)
+        .map(|k0| {
+            let chi: f64 = counts.iter().zip(rel).map(|(c, &r)| chi_squared_for_shift(c, (k0 + r) % 26)).sum();
+            (k0, chi)
+        })
+        .fold((0, f64::INFINITY), |b, x| if x.1 < b.1 { x } else { b })
+}
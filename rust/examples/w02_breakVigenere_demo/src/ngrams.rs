@@ -0,0 +1,81 @@
+// ngrams.rs
+// Loading a full quadgram table from disk, and a dense form for hot loops.
+//
+// File format (as distributed by Practical Cryptography): one "TION 13168375" per line,
+// i.e. a 4-letter gram and its count in a large English corpus.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Log10 score of a quadgram never seen in the table (same floor as `quadgram_score`)
+pub const QUADGRAM_FLOOR: f64 = -11.0;
+
+/// Fewer quadgrams than this can only be the built-in seed table (a full one has ~390,000)
+const FULL_TABLE_MIN: usize = 1000;
+
+/// Warn that results are rough when `quadgrams` is the built-in seed table.
+pub fn note_if_builtin(quadgrams: &HashMap<[u8; 4], f64>) {
+    if quadgrams.len() < FULL_TABLE_MIN {
+        println!("Note: the built-in quadgram table is tiny; pass --quadgrams with a full table for real attacks.");
+    }
+}
+
+/// Read a quadgram counts file into log10 probabilities, the format `quadgram_score` expects.
+pub fn load_quadgrams(path: &Path) -> io::Result<HashMap<[u8; 4], f64>> {
+    let text = fs::read_to_string(path)?;
+    let mut counts: Vec<([u8; 4], f64)> = Vec::new();
+    for (lineno, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let bad = || io::Error::new(io::ErrorKind::InvalidData, format!("line {}: expected 'ABCD <count>'", lineno + 1));
+        let (gram, count) = line.split_once(char::is_whitespace).ok_or_else(bad)?;
+        let gram = gram.as_bytes();
+        if gram.len() != 4 || !gram.iter().all(|b| b.is_ascii_alphabetic()) {
+            return Err(bad());
+        }
+        let count: f64 = count.trim().parse().map_err(|_| bad())?;
+        let mut key = [0u8; 4];
+        for (k, g) in key.iter_mut().zip(gram) {
+            *k = g.to_ascii_uppercase();
+        }
+        counts.push((key, count));
+    }
+
+    let total: f64 = counts.iter().map(|(_, c)| c).sum();
+    if total <= 0.0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "quadgram file has no counts"));
+    }
+    Ok(counts.into_iter().map(|(k, c)| (k, (c / total).log10())).collect())
+}
+
+/// Dense 26^4 array of log10 probabilities, indexed by `quad_index`; unseen grams get the floor.
+pub fn dense_quadgrams(table: &HashMap<[u8; 4], f64>) -> Vec<f64> {
+    let mut dense = vec![QUADGRAM_FLOOR; 26 * 26 * 26 * 26];
+    for (q, &v) in table.iter() {
+        if q.iter().all(|b| b.is_ascii_uppercase()) {
+            let idx = quad_index([q[0] - b'A', q[1] - b'A', q[2] - b'A', q[3] - b'A']);
+            dense[idx] = v;
+        }
+    }
+    dense
+}
+
+/// Index of four letters 0..26 into the dense table
+pub fn quad_index(q: [u8; 4]) -> usize {
+    ((q[0] as usize * 26 + q[1] as usize) * 26 + q[2] as usize) * 26 + q[3] as usize
+}
+
+/// Quadgram score of letters already mapped to 0..26 (same sum as `quadgram_score`)
+pub fn score_indices(dense: &[f64], letters: &[u8]) -> f64 {
+    if letters.len() < 4 {
+        return f64::NEG_INFINITY;
+    }
+    letters
+        .windows(4)
+        .map(|w| dense[quad_index([w[0], w[1], w[2], w[3]])])
+        .sum()
+}
@@ -0,0 +1,311 @@
+// quagmire.rs
+// Quagmire I-IV: Vigenere with mixed (keyword) alphabets on the tableau.
+//
+//   I   keyed plaintext alphabet,  straight ciphertext alphabet
+//   II  straight plaintext,        keyed ciphertext alphabet
+//   III the same keyed alphabet on both sides
+//   IV  two different keyed alphabets
+//
+// Column j of the period slides the ciphertext alphabet so that indicator letter j
+// sits under plaintext 'A'. With both alphabets straight this is exactly Vigenere.
+//
+// Attack: the period survives the mixing, so Kasiski + periodic IoC still find it.
+// The alphabets and indicator are then recovered by hill-climbing on quadgram fitness.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::cli::{Cli, QuagmireArgs};
+use crate::ngrams::{dense_quadgrams, note_if_builtin, score_indices};
+use crate::report::{print_json, PeriodIoc, QuagmireCandidate, QuagmireReport};
+use crate::segment::Segmenter;
+use crate::{clean_text, estimate_period, EN_FREQ};
+
+const STRAIGHT: [u8; 26] = {
+    let mut a = [0u8; 26];
+    let mut i = 0;
+    while i < 26 {
+        a[i] = i as u8;
+        i += 1;
+    }
+    a
+};
+
+/// A Quagmire key: both alphabets (letters as 0..26) and one slide per period column
+#[derive(Clone, Debug)]
+pub struct QuagmireKey {
+    pub pt: [u8; 26],
+    pub ct: [u8; 26],
+    pub shifts: Vec<u8>,
+}
+
+impl QuagmireKey {
+    /// Build the key for `variant` from keyword(s) and the indicator key.
+    pub fn new(variant: u8, keyword: &str, ct_keyword: &str, indicator: &str) -> Self {
+        let (pt, ct) = match variant {
+            1 => (keyed_alphabet(keyword), STRAIGHT),
+            2 => (STRAIGHT, keyed_alphabet(keyword)),
+            3 => (keyed_alphabet(keyword), keyed_alphabet(keyword)),
+            _ => (keyed_alphabet(keyword), keyed_alphabet(ct_keyword)),
+        };
+        let pt_a = inverse(&pt)[0];
+        let ct_inv = inverse(&ct);
+        let shifts = indicator
+            .bytes()
+            .map(|k| (ct_inv[(k - b'A') as usize] + 26 - pt_a) % 26)
+            .collect();
+        QuagmireKey { pt, ct, shifts }
+    }
+
+    pub fn encrypt(&self, plain: &str) -> String {
+        let pt_inv = inverse(&self.pt);
+        let period = self.shifts.len();
+        plain
+            .bytes()
+            .enumerate()
+            .map(|(i, b)| {
+                let idx = (pt_inv[(b - b'A') as usize] + self.shifts[i % period]) % 26;
+                (b'A' + self.ct[idx as usize]) as char
+            })
+            .collect()
+    }
+
+    pub fn decrypt(&self, cipher: &str) -> String {
+        let c: Vec<u8> = cipher.bytes().map(|b| b - b'A').collect();
+        let mut out = Vec::with_capacity(c.len());
+        self.decrypt_indices(&c, &mut out);
+        out.iter().map(|&x| (b'A' + x) as char).collect()
+    }
+
+    /// Decrypt letters 0..26 into `out` (reused buffer for the hill-climb)
+    fn decrypt_indices(&self, c: &[u8], out: &mut Vec<u8>) {
+        let ct_inv = inverse(&self.ct);
+        let period = self.shifts.len();
+        out.clear();
+        out.extend(c.iter().enumerate().map(|(i, &x)| {
+            let idx = (ct_inv[x as usize] + 26 - self.shifts[i % period]) % 26;
+            self.pt[idx as usize]
+        }));
+    }
+
+    /// Indicator key: the ciphertext letter under plaintext 'A' in each column
+    pub fn indicator(&self) -> String {
+        let pt_a = inverse(&self.pt)[0];
+        self.shifts
+            .iter()
+            .map(|&s| (b'A' + self.ct[((pt_a + s) % 26) as usize]) as char)
+            .collect()
+    }
+}
+
+/// Keyword letters (first occurrence only) followed by the rest of A-Z, as 0..26
+pub fn keyed_alphabet(keyword: &str) -> [u8; 26] {
+    let mut out = Vec::with_capacity(26);
+    for b in clean_text(keyword).bytes().map(|b| b - b'A').chain(0..26) {
+        if !out.contains(&b) {
+            out.push(b);
+        }
+    }
+    out.try_into().expect("26 distinct letters")
+}
+
+fn inverse(alpha: &[u8; 26]) -> [u8; 26] {
+    let mut inv = [0u8; 26];
+    for (i, &a) in alpha.iter().enumerate() {
+        inv[a as usize] = i as u8;
+    }
+    inv
+}
+
+fn alphabet_string(alpha: &[u8; 26]) -> String {
+    alpha.iter().map(|&x| (b'A' + x) as char).collect()
+}
+
+/// Entry point for `quagmire`: encrypt/decrypt with a known key, or attack.
+pub fn run(cli: &Cli, args: &QuagmireArgs, text: &str, quadgrams: &HashMap<[u8; 4], f64>, words: &Segmenter) {
+    if let Some(indicator) = &args.indicator {
+        let indicator = clean_text(indicator);
+        let keyword = args.keyword.as_deref().unwrap_or("");
+        if indicator.is_empty() || (args.variant == 4 && args.ct_keyword.is_none()) {
+            eprintln!("Need a non-empty --indicator, and --ct-keyword for variant 4");
+            std::process::exit(1);
+        }
+        let key = QuagmireKey::new(args.variant, keyword, args.ct_keyword.as_deref().unwrap_or(""), &indicator);
+        let (label, out) = if args.decrypt {
+            ("plaintext", key.decrypt(text))
+        } else {
+            ("ciphertext", key.encrypt(text))
+        };
+        if cli.json {
+            print_json(&serde_json::json!({ label: out }));
+        } else {
+            println!("{}", out);
+        }
+        return;
+    }
+
+    let (estimated, scores) = estimate_period(text, 20);
+    let period = args.period.unwrap_or(estimated);
+    let dense = dense_quadgrams(quadgrams);
+    let mut rng = rand::thread_rng();
+
+    let mut candidates: Vec<QuagmireCandidate> = (0..args.restarts.max(1))
+        .map(|_| {
+            let (key, fitness) = climb(text, args.variant, period, &dense, args.iterations, &mut rng);
+            QuagmireCandidate {
+                plaintext_alphabet: alphabet_string(&key.pt),
+                ciphertext_alphabet: alphabet_string(&key.ct),
+                indicator: key.indicator(),
+                fitness,
+                plaintext: key.decrypt(text),
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    candidates.dedup_by(|a, b| a.plaintext == b.plaintext);
+    candidates.truncate(cli.top);
+
+    let report = QuagmireReport {
+        variant: args.variant,
+        period,
+        period_ioc: scores.into_iter().map(|(period, ioc)| PeriodIoc { period, ioc }).collect(),
+        candidates,
+    };
+    if cli.json {
+        print_json(&report);
+        return;
+    }
+
+    println!("Quagmire {} attack, {} letters", report.variant, text.len());
+    println!("Periodic IoC (English ~0.066, random ~0.038):");
+    for p in report.period_ioc.iter() {
+        println!("  {:2} -> {:.4}", p.period, p.ioc);
+    }
+    println!("Using period {}\n", report.period);
+    for c in report.candidates.iter() {
+        println!("fitness {:9.2} | indicator: {}", c.fitness, c.indicator);
+        println!("  plain alphabet : {}", c.plaintext_alphabet);
+        println!("  cipher alphabet: {}", c.ciphertext_alphabet);
+        println!("Plaintext:\n{}\n", words.readable(&c.plaintext).0);
+    }
+    note_if_builtin(quadgrams);
+}
+
+/// One hill-climb. Keyword alphabets are mostly A-Z in order (only the keyword letters
+/// move), so start from straight alphabets disturbed by a few random steps, and climb with
+/// two kinds of step: swap two letters, or move one letter elsewhere in the alphabet.
+/// For each trial alphabet the slides are not searched: each column takes the slide whose
+/// letter counts best match English (chi-squared, as in `best_shift_for_column`).
+/// Finally polish the slides one column at a time on quadgram fitness.
+///
+/// Quagmire I falls quickly: with a straight ciphertext alphabet the slides are right from
+/// the start. II-IV are much harder (a wrong alphabet also throws the slides off); expect to
+/// need long ciphertexts, a full quadgram table and many restarts.
+fn climb<R: Rng>(cipher: &str, variant: u8, period: usize, dense: &[f64], iterations: usize, rng: &mut R) -> (QuagmireKey, f64) {
+    let c: Vec<u8> = cipher.bytes().map(|b| b - b'A').collect();
+
+    let mut counts = vec![[0usize; 26]; period];
+    for (i, &x) in c.iter().enumerate() {
+        counts[i % period][x as usize] += 1;
+    }
+    let fit_slides = |k: &mut QuagmireKey| {
+        for (col, cnt) in counts.iter().enumerate() {
+            k.shifts[col] = best_slide(cnt, &k.pt, &k.ct);
+        }
+    };
+    let mut buf = Vec::with_capacity(c.len());
+    let mut eval = |k: &QuagmireKey| {
+        k.decrypt_indices(&c, &mut buf);
+        score_indices(dense, &buf)
+    };
+
+    let mut key = QuagmireKey { pt: STRAIGHT, ct: STRAIGHT, shifts: vec![0; period] };
+    for _ in 0..rng.gen_range(0..8) {
+        let (side, i, j) = (rng.gen_range(0..2), rng.gen_range(0..26), rng.gen_range(0..26));
+        mutate(&mut key, variant, side, rng.gen_bool(0.5), i, j);
+    }
+    fit_slides(&mut key);
+    let mut best = eval(&key);
+
+    for _ in 0..iterations {
+        let mut cand = key.clone();
+        let (side, i, j) = (rng.gen_range(0..2), rng.gen_range(0..26), rng.gen_range(0..26));
+        if i == j || !mutate(&mut cand, variant, side, rng.gen_bool(0.5), i, j) {
+            continue;
+        }
+        fit_slides(&mut cand);
+        let f = eval(&cand);
+        if f >= best {
+            best = f;
+            key = cand;
+        }
+    }
+
+    for col in 0..period {
+        let mut cand = key.clone();
+        for s in 0..26 {
+            cand.shifts[col] = s;
+            let f = eval(&cand);
+            if f > best {
+                best = f;
+                key.shifts[col] = s;
+            }
+        }
+    }
+    (key, best)
+}
+
+/// Change the plaintext (side 0) or ciphertext (side 1) alphabet, if that alphabet is free
+/// in this variant: swap positions i and j, or move the letter at i to position j.
+/// Variant III keeps both sides identical.
+fn mutate(key: &mut QuagmireKey, variant: u8, side: usize, swap: bool, i: usize, j: usize) -> bool {
+    let step = |alpha: &mut [u8; 26]| {
+        if swap {
+            alpha.swap(i, j);
+        } else if i < j {
+            alpha[i..=j].rotate_left(1);
+        } else {
+            alpha[j..=i].rotate_right(1);
+        }
+    };
+    match (side, variant) {
+        (0, 2) | (1, 1) | (1, 3) => false,
+        (0, _) => {
+            step(&mut key.pt);
+            if variant == 3 {
+                key.ct = key.pt;
+            }
+            true
+        }
+        _ => {
+            step(&mut key.ct);
+            true
+        }
+    }
+}
+
+/// Slide for one column whose decryption has the lowest chi-squared against English
+fn best_slide(counts: &[usize; 26], pt: &[u8; 26], ct: &[u8; 26]) -> u8 {
+    let ct_inv = inverse(ct);
+    let n: usize = counts.iter().sum();
+    let mut best = (0u8, f64::INFINITY);
+    for s in 0..26u8 {
+        let mut obs = [0usize; 26];
+        for (x, &cnt) in counts.iter().enumerate() {
+            obs[pt[((ct_inv[x] + 26 - s) % 26) as usize] as usize] += cnt;
+        }
+        let chi: f64 = obs
+            .iter()
+            .zip(EN_FREQ.iter())
+            .map(|(&o, &f)| {
+                let expected = f * n as f64;
+                (o as f64 - expected).powi(2) / expected
+            })
+            .sum();
+        if chi < best.1 {
+            best = (s, chi);
+        }
+    }
+    best.0
+}
@@ -0,0 +1,311 @@
+// report.rs
+// Machine-readable result of one breaker run (emitted with --json).
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::confidence::{Calibration, Confidence};
+use crate::suffix::Repeats;
+
+/// Everything the breaker computed, in a stable order.
+#[derive(Serialize, Debug)]
+pub struct Report {
+    pub ciphertext_length: usize,
+    pub scorer: &'static str,
+    pub column_solver: &'static str,
+    /// Number of maximal repeats; only the longest are listed in `repeats`
+    pub repeat_count: usize,
+    pub repeats: Vec<Repeat>,
+    pub distances: Vec<usize>,
+    pub factor_counts: Vec<FactorCount>,
+    pub key_lengths_tried: Vec<usize>,
+    pub candidates: Vec<Candidate>,
+    pub calibration: Calibration,
+    /// "credible solution: key ..." or "no credible solution"
+    pub verdict: String,
+}
+
+/// A maximal repeat, how often it occurs and its first positions.
+#[derive(Serialize, Debug)]
+pub struct Repeat {
+    /// The repeated text, cut to `REPEAT_TEXT` letters and "..." when longer
+    pub ngram: String,
+    pub length: usize,
+    pub occurrences: usize,
+    pub positions: Vec<usize>,
+}
+
+/// How many Kasiski distances a factor divides.
+#[derive(Serialize, Debug)]
+pub struct FactorCount {
+    pub factor: usize,
+    pub count: usize,
+}
+
+/// One candidate decryption. `chi_squared` is lower-better, `fitness` and
+/// `word_coverage` (share of letters in dictionary words) higher-better.
+#[derive(Serialize, Debug, Clone)]
+pub struct Candidate {
+    pub key_length: usize,
+    pub key: String,
+    pub chi_squared: f64,
+    pub fitness: f64,
+    pub word_coverage: f64,
+    pub confidence: Confidence,
+    pub plaintext: String,
+    pub segmented: String,
+}
+
+/// Letters of a repeat's text kept in the report
+const REPEAT_TEXT: usize = 40;
+
+/// The listed repeats of `text`, longest first, then by first position.
+pub fn sorted_repeats(repeats: &Repeats, text: &str) -> Vec<Repeat> {
+    let mut out: Vec<Repeat> = repeats
+        .longest
+        .iter()
+        .map(|r| Repeat {
+            ngram: if r.len > REPEAT_TEXT {
+                format!("{}...", &text[r.positions[0]..r.positions[0] + REPEAT_TEXT])
+            } else {
+                text[r.positions[0]..r.positions[0] + r.len].to_string()
+            },
+            length: r.len,
+            occurrences: r.occurrences,
+            positions: r.positions.clone(),
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        b.length.cmp(&a.length)
+            .then_with(|| a.positions[0].cmp(&b.positions[0]))
+            .then_with(|| a.ngram.cmp(&b.ngram))
+    });
+    out
+}
+
+/// Factor counts sorted by count descending, ties broken by the smaller factor.
+pub fn sorted_factors(counts: HashMap<usize, usize>) -> Vec<FactorCount> {
+    let mut out: Vec<FactorCount> = counts
+        .into_iter()
+        .map(|(factor, count)| FactorCount { factor, count })
+        .collect();
+    out.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.factor.cmp(&b.factor)));
+    out
+}
+
+/// Result of the running-key attack.
+#[derive(Serialize, Debug)]
+pub struct RunningKeyReport {
+    pub ciphertext_length: usize,
+    pub beam_width: usize,
+    pub candidates: Vec<RunningKeyCandidate>,
+}
+
+/// One split of the ciphertext into two English-looking streams.
+#[derive(Serialize, Debug, Clone)]
+pub struct RunningKeyCandidate {
+    pub plaintext: String,
+    pub key: String,
+    pub fitness: f64,
+}
+
+/// Result of the Quagmire attack.
+#[derive(Serialize, Debug)]
+pub struct QuagmireReport {
+    pub variant: u8,
+    pub period: usize,
+    pub period_ioc: Vec<PeriodIoc>,
+    pub candidates: Vec<QuagmireCandidate>,
+}
+
+/// Average column IoC for one trial period.
+#[derive(Serialize, Debug)]
+pub struct PeriodIoc {
+    pub period: usize,
+    pub ioc: f64,
+}
+
+/// Best key found by one hill-climbing restart.
+#[derive(Serialize, Debug, Clone)]
+pub struct QuagmireCandidate {
+    pub plaintext_alphabet: String,
+    pub ciphertext_alphabet: String,
+    pub indicator: String,
+    pub fitness: f64,
+    pub plaintext: String,
+}
+
+/// Result of dragging a crib along the ciphertext.
+#[derive(Serialize, Debug)]
+pub struct CribReport {
+    pub crib: String,
+    pub offsets_tried: usize,
+    pub periodic_offsets: usize,
+    pub candidates: Vec<CribCandidate>,
+}
+
+/// One crib position whose implied key fragment is periodic.
+#[derive(Serialize, Debug, Clone)]
+pub struct CribCandidate {
+    pub offset: usize,
+    pub key_fragment: String,
+    pub key_length: usize,
+    pub confirmations: usize,
+    pub key: String,
+    pub fitness: f64,
+    pub word_coverage: f64,
+    pub plaintext: String,
+    pub segmented: String,
+}
+
+/// Result of the wordlist attack.
+#[derive(Serialize, Debug)]
+pub struct DictReport {
+    /// Lengths a word had to have to be tried; empty means any
+    pub key_lengths: Vec<usize>,
+    pub words_read: usize,
+    pub words_tried: usize,
+    pub words_abandoned: usize,
+    pub candidates: Vec<DictCandidate>,
+}
+
+/// One wordlist key and its decryption.
+#[derive(Serialize, Debug, Clone)]
+pub struct DictCandidate {
+    pub key: String,
+    pub fitness: f64,
+    pub word_coverage: f64,
+    pub plaintext: String,
+    pub segmented: String,
+}
+
+/// Statistics of an unknown ciphertext and the cipher family they point to.
+#[derive(Serialize, Debug)]
+pub struct IdentifyReport {
+    /// "letters" or "hex bytes"
+    pub input_kind: &'static str,
+    pub length: usize,
+    pub alphabet_size: usize,
+    pub ioc: f64,
+    /// Letter-only features (absent for byte input)
+    pub letters: Option<LetterFeatures>,
+    pub family: &'static str,
+    pub reasons: Vec<String>,
+}
+
+/// Features computed on letter ciphertexts.
+#[derive(Serialize, Debug)]
+pub struct LetterFeatures {
+    pub missing_letters: String,
+    pub best_shift: usize,
+    pub best_shift_chi_per_letter: f64,
+    pub sorted_chi_per_letter: f64,
+    pub period: usize,
+    pub period_ioc: Vec<PeriodIoc>,
+    pub top_factors: Vec<FactorCount>,
+    /// Coincidence rate of overlapping letter pairs
+    pub bigram_ioc: f64,
+    /// Chi-squared against English digraph frequencies, per bigram
+    pub bigram_chi_per_bigram: f64,
+    pub doubled_letters: usize,
+    pub doubled_in_pairs: usize,
+    pub even_length: bool,
+}
+
+/// Result of the homophonic attack.
+#[derive(Serialize, Debug)]
+pub struct HomophonicReport {
+    pub ciphertext_length: usize,
+    pub distinct_codes: usize,
+    /// IoC over codes; flat (about 1 / codes) when the key follows English frequencies
+    pub code_ioc: f64,
+    pub top_code_share: f64,
+    pub candidates: Vec<HomophonicCandidate>,
+}
+
+/// Best key from one restart: "E 3 17 42" per letter.
+#[derive(Serialize, Debug, Clone)]
+pub struct HomophonicCandidate {
+    pub fitness: f64,
+    pub key: Vec<String>,
+    pub plaintext: String,
+}
+
+/// Result of the ADFGVX attack.
+#[derive(Serialize, Debug)]
+pub struct AdfgvxReport {
+    pub width: usize,
+    /// Best digraph IoC reached at each trial width
+    pub widths: Vec<WidthScore>,
+    /// Ciphertext column read into each plaintext column
+    pub column_order: Vec<usize>,
+    /// Distinct label pairs seen; more than 26 means the plaintext held digits
+    pub distinct_pairs: usize,
+    pub candidates: Vec<AdfgvxCandidate>,
+}
+
+/// How well one transposition width turns the ciphertext back into Polybius digraphs.
+#[derive(Serialize, Debug)]
+pub struct WidthScore {
+    pub width: usize,
+    pub digraph_ioc: f64,
+}
+
+/// Substitution layer from one restart; the square may come out transposed.
+#[derive(Serialize, Debug, Clone)]
+pub struct AdfgvxCandidate {
+    pub fitness: f64,
+    /// 36 cells row by row, '?' for digraphs that never occur; letters only, so the
+    /// cells of any digits in the plaintext hold a letter too
+    pub square: String,
+    pub plaintext: String,
+}
+
+/// Unicity distances and how the breakers fare around them.
+#[derive(Serialize, Debug)]
+pub struct UnicityReport {
+    pub quadgrams: usize,
+    pub language: LanguageEntropy,
+    pub solved_share: f64,
+    pub trials_per_length: usize,
+    pub ciphers: Vec<UnicityRow>,
+}
+
+/// Entropy of English per letter (and per raw byte), in bits.
+#[derive(Serialize, Debug)]
+pub struct LanguageEntropy {
+    /// From single-letter frequencies only
+    pub unigram_bits: f64,
+    /// Of a letter given the three before it, from the quadgram table
+    pub model_bits: f64,
+    /// Of a raw byte of the sample: its class (case, space, punctuation) plus `model_bits`
+    /// for each letter
+    pub byte_bits: f64,
+}
+
+/// One cipher: key space, redundancy per symbol and unicity distance.
+#[derive(Serialize, Debug)]
+pub struct UnicityRow {
+    pub cipher: String,
+    pub key_space_bits: f64,
+    pub alphabet_bits: f64,
+    pub redundancy: f64,
+    pub unicity_distance: f64,
+    pub trials: Vec<UnicityTrial>,
+}
+
+/// Share of random trials of one length the breaker solved.
+#[derive(Serialize, Debug)]
+pub struct UnicityTrial {
+    /// Length as a multiple of the unicity distance
+    pub multiple: f64,
+    pub length: usize,
+    pub success_rate: f64,
+}
+
+/// Pretty-print any report as JSON on stdout.
+pub fn print_json<T: Serialize>(value: &T) {
+    let json = serde_json::to_string_pretty(value).expect("report serializes");
+    println!("{}", json);
+}
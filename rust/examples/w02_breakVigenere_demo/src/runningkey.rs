@@ -0,0 +1,165 @@
+// runningkey.rs
+// Running-key cipher: a Vigenere whose key is a passage of ordinary text (e.g. from a book)
+// as long as the message. There is no period, so Kasiski and the per-column chi-squared
+// solve see nothing. But both plaintext and key are English, so we search for the split
+// c = p + k where *both* streams score well under the quadgram model.
+//
+// Attack: Viterbi over "last three plaintext letters" states (26^3), pruned to a beam
+// once the first quadgram has been scored.
+// Given the ciphertext, the last three key letters follow from the last three plaintext
+// letters, so two paths in the same state have the same future and only the better one
+// is kept.
+
+use std::collections::HashMap;
+use std::fs;
+
+use crate::cli::{Cli, RunningKeyArgs};
+use crate::ngrams::{dense_quadgrams, note_if_builtin, quad_index};
+use crate::report::{print_json, RunningKeyCandidate, RunningKeyReport};
+use crate::segment::Segmenter;
+use crate::{clean_text, vigenere_decrypt, vigenere_encrypt};
+
+/// Number of states: three plaintext letters of context
+const CONTEXTS: usize = 26 * 26 * 26;
+
+/// Entry point for `running-key`: encrypt/decrypt with a key file, or attack.
+pub fn run(cli: &Cli, args: &RunningKeyArgs, text: &str, quadgrams: &HashMap<[u8; 4], f64>, words: &Segmenter) {
+    if let Some(path) = args.encrypt.as_ref().or(args.decrypt.as_ref()) {
+        let key = match fs::read_to_string(path) {
+            Ok(k) => clean_text(&k),
+            Err(e) => {
+                eprintln!("Failed to read key text {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        };
+        let key = match running_key(&key, args.offset, text.len()) {
+            Some(k) => k,
+            None => {
+                eprintln!(
+                    "Key text has {} letters after offset {}, message needs {}",
+                    key.len().saturating_sub(args.offset), args.offset, text.len()
+                );
+                std::process::exit(1);
+            }
+        };
+        let (label, out) = if args.encrypt.is_some() {
+            ("ciphertext", vigenere_encrypt(text, key))
+        } else {
+            ("plaintext", vigenere_decrypt(text, key))
+        };
+        if cli.json {
+            print_json(&serde_json::json!({ label: out }));
+        } else {
+            println!("{}", out);
+        }
+        return;
+    }
+
+    let dense = dense_quadgrams(quadgrams);
+    let candidates = beam_attack(text, &dense, args.beam, cli.top);
+    let report = RunningKeyReport { ciphertext_length: text.len(), beam_width: args.beam, candidates };
+
+    if cli.json {
+        print_json(&report);
+        return;
+    }
+    println!("Running-key attack: {} letters, beam width {}", report.ciphertext_length, report.beam_width);
+    println!("(The two streams are interchangeable: the cipher cannot tell plaintext from key.)\n");
+    for (i, c) in report.candidates.iter().enumerate() {
+        println!("#{:<2} fitness {:9.2}", i + 1, c.fitness);
+        println!("  plaintext: {}", words.readable(&c.plaintext).0);
+        println!("  key      : {}\n", words.readable(&c.key).0);
+    }
+    note_if_builtin(quadgrams);
+}
+
+/// The slice of cleaned key text used for a message of `len` letters, if long enough
+fn running_key(key: &str, offset: usize, len: usize) -> Option<&str> {
+    key.get(offset..offset.checked_add(len)?)
+}
+
+/// Split `cipher` into the `top` best (plaintext, key) pairs, both scored as English.
+pub fn beam_attack(cipher: &str, dense: &[f64], width: usize, top: usize) -> Vec<RunningKeyCandidate> {
+    let c: Vec<u8> = cipher.bytes().map(|b| b - b'A').collect();
+    let width = width.max(1);
+
+    // Back-pointer arena: (plaintext letter, parent node); u32::MAX marks the start
+    let mut arena: Vec<(u8, u32)> = Vec::new();
+    // Beam entries: (context = last three plaintext letters, score, node)
+    let mut beam: Vec<(usize, f64, u32)> = vec![(0, 0.0, u32::MAX)];
+
+    let mut best: Vec<Option<(f64, u32, u8)>> = vec![None; CONTEXTS];
+    let mut touched: Vec<usize> = Vec::new();
+
+    for i in 0..c.len() {
+        for &(ctx, score, node) in beam.iter() {
+            let prev_p = [(ctx / 676) as u8, (ctx / 26 % 26) as u8, (ctx % 26) as u8];
+            let prev_k = if i >= 3 {
+                [
+                    (c[i - 3] + 26 - prev_p[0]) % 26,
+                    (c[i - 2] + 26 - prev_p[1]) % 26,
+                    (c[i - 1] + 26 - prev_p[2]) % 26,
+                ]
+            } else {
+                [0; 3]
+            };
+            for p in 0..26u8 {
+                let k = (c[i] + 26 - p) % 26;
+                let mut s = score;
+                if i >= 3 {
+                    s += dense[quad_index([prev_p[0], prev_p[1], prev_p[2], p])];
+                    s += dense[quad_index([prev_k[0], prev_k[1], prev_k[2], k])];
+                }
+                let next = (ctx * 26 + p as usize) % CONTEXTS;
+                match best[next] {
+                    Some((b, _, _)) if b >= s => {}
+                    slot => {
+                        if slot.is_none() {
+                            touched.push(next);
+                        }
+                        best[next] = Some((s, node, p));
+                    }
+                }
+            }
+        }
+
+        // Keep the `width` best states. Quadgrams only start scoring at i = 3; before then
+        // every state is 0.0, so cutting would keep an arbitrary subset of the 26^3 openings.
+        let mut next_beam: Vec<(usize, f64, u32)> = Vec::with_capacity(touched.len());
+        for &ctx in touched.iter() {
+            let (s, parent, p) = best[ctx].take().expect("touched state has an entry");
+            arena.push((p, parent));
+            next_beam.push((ctx, s, (arena.len() - 1) as u32));
+        }
+        touched.clear();
+        if i >= 3 && next_beam.len() > width {
+            next_beam.select_nth_unstable_by(width - 1, |a, b| b.1.total_cmp(&a.1));
+            next_beam.truncate(width);
+        }
+        beam = next_beam;
+    }
+
+    beam.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut out: Vec<RunningKeyCandidate> = Vec::new();
+    for &(_, score, node) in beam.iter() {
+        if out.len() >= top {
+            break;
+        }
+        let mut p: Vec<u8> = Vec::with_capacity(c.len());
+        let mut n = node;
+        while n != u32::MAX {
+            let (letter, parent) = arena[n as usize];
+            p.push(letter);
+            n = parent;
+        }
+        p.reverse();
+        let plaintext: String = p.iter().map(|&x| (b'A' + x) as char).collect();
+        let key: String = c.iter().zip(p.iter()).map(|(&ci, &pi)| (b'A' + (ci + 26 - pi) % 26) as char).collect();
+        // The swapped split scores the same; report each pair once
+        if out.iter().any(|o| o.plaintext == key && o.key == plaintext) {
+            continue;
+        }
+        out.push(RunningKeyCandidate { plaintext, key, fitness: score });
+    }
+    out
+}
@@ -0,0 +1,181 @@
+// segment.rs
+// Put the spaces back: clean_text throws away word boundaries, so a recovered
+// "THEREWASNOTHING" is hard to read. Under a unigram model each word is independent,
+// P(w1 w2 ... wn) = P(w1) P(w2) ... P(wn), and the most likely split is found by dynamic
+// programming: best[i] = max over j of best[j] + log P(text[j..i]).
+//
+// Unknown words get a probability 1000 times below the rarest known word, and 10 times
+// lower again per letter (Norvig's 10 / (N * 10^len) for a corpus of N words), so a long
+// run of gibberish is never cheaper than the dictionary words it could be split into.
+//
+// Word file format: one word per line, most frequent first, optionally followed by its
+// count ("the 23135851162", as in Norvig's count_1w.txt). Without counts the ranks are
+// turned into frequencies with Zipf's law.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Longest unknown word considered (dictionary words may be longer)
+const MAX_UNKNOWN: usize = 20;
+
+/// A few hundred of the most common English words, most frequent first
+const BUILTIN_WORDS: &str = "
+THE OF AND TO A IN IS IT YOU THAT HE WAS FOR ON ARE WITH AS I HIS THEY BE AT ONE HAVE
+THIS FROM OR HAD BY NOT WORD BUT WHAT SOME WE CAN OUT OTHER WERE ALL THERE WHEN UP USE
+YOUR HOW SAID AN EACH SHE WHICH DO THEIR TIME IF WILL WAY ABOUT MANY THEN THEM WRITE
+WOULD LIKE SO THESE HER LONG MAKE THING SEE HIM TWO HAS LOOK MORE DAY COULD GO COME DID
+NUMBER SOUND NO MOST PEOPLE MY OVER KNOW WATER THAN CALL FIRST WHO MAY DOWN SIDE BEEN
+NOW FIND ANY NEW WORK PART TAKE GET PLACE MADE LIVE WHERE AFTER BACK LITTLE ONLY ROUND
+MAN YEAR CAME SHOW EVERY GOOD ME GIVE OUR UNDER NAME VERY THROUGH JUST FORM SENTENCE
+GREAT THINK SAY HELP LOW LINE DIFFER TURN CAUSE MUCH MEAN BEFORE MOVE RIGHT BOY OLD TOO
+SAME TELL DOES SET THREE WANT AIR WELL ALSO PLAY SMALL END PUT HOME READ HAND PORT LARGE
+SPELL ADD EVEN LAND HERE MUST BIG HIGH SUCH FOLLOW ACT WHY ASK MEN CHANGE WENT LIGHT KIND
+OFF NEED HOUSE PICTURE TRY US AGAIN ANIMAL POINT MOTHER WORLD NEAR BUILD SELF EARTH
+FATHER HEAD STAND OWN PAGE SHOULD COUNTRY FOUND ANSWER SCHOOL GROW STUDY STILL LEARN
+PLANT COVER FOOD SUN FOUR BETWEEN STATE KEEP EYE NEVER LAST LET THOUGHT CITY TREE CROSS
+FARM HARD START MIGHT STORY SAW FAR SEA DRAW LEFT LATE RUN WHILE PRESS CLOSE NIGHT REAL
+LIFE FEW NORTH OPEN SEEM TOGETHER NEXT WHITE CHILDREN BEGIN GOT WALK EXAMPLE EASE PAPER
+GROUP ALWAYS MUSIC THOSE BOTH MARK OFTEN LETTER UNTIL MILE RIVER CAR FEET CARE SECOND
+BOOK CARRY TOOK SCIENCE EAT ROOM FRIEND BEGAN IDEA FISH MOUNTAIN STOP ONCE BASE HEAR HORSE
+CUT SURE WATCH COLOR FACE WOOD MAIN ENOUGH PLAIN GIRL USUAL YOUNG READY ABOVE EVER RED
+LIST THOUGH FEEL TALK BIRD SOON BODY DOG FAMILY DIRECT POSE LEAVE SONG MEASURE DOOR
+PRODUCT BLACK SHORT NUMERAL CLASS WIND QUESTION HAPPEN COMPLETE SHIP AREA HALF ROCK ORDER
+FIRE SOUTH PROBLEM PIECE TOLD KNEW PASS SINCE TOP WHOLE KING SPACE HEARD BEST HOUR BETTER
+TRUE DURING HUNDRED FIVE REMEMBER STEP EARLY HOLD WEST GROUND INTEREST REACH FAST VERB
+SING LISTEN SIX TABLE TRAVEL LESS MORNING TEN SIMPLE SEVERAL TOWARD WAR LAY AGAINST
+PATTERN SLOW CENTER LOVE PERSON MONEY SERVE APPEAR ROAD MAP RAIN RULE GOVERN PULL COLD
+NOTICE VOICE UNIT POWER TOWN FINE CERTAIN FLY FALL LEAD CRY DARK MACHINE NOTE WAIT PLAN
+FIGURE STAR BOX NOUN FIELD REST CORRECT ABLE POUND DONE BEAUTY DRIVE STOOD CONTAIN FRONT
+TEACH WEEK FINAL GAVE GREEN OH QUICK DEVELOP OCEAN WARM FREE MINUTE STRONG SPECIAL MIND
+BEHIND CLEAR TAIL PRODUCE FACT STREET INCH MULTIPLY NOTHING COURSE STAY WHEEL FULL FORCE
+BLUE OBJECT DECIDE SURFACE DEEP MOON ISLAND FOOT SYSTEM BUSY TEST RECORD BOAT COMMON GOLD
+POSSIBLE PLANE STEAD DRY WONDER LAUGH THOUSAND AGO RAN CHECK GAME SHAPE EQUATE HOT MISS
+BROUGHT HEAT SNOW TIRE BRING YES DISTANT FILL EAST PAINT LANGUAGE AMONG WORST AGE TIMES
+SEASON SPRING WINTER HOPE DESPAIR HEAVEN PERIOD PRESENT EVIL DEGREE QUEEN ENGLAND FRANCE
+SECRET ATTACK DAWN MESSAGE KEY CIPHER CODE ENEMY ARMY SEND MEET TONIGHT TOMORROW
+";
+
+/// Unigram word model used to segment plaintext.
+pub struct Segmenter {
+    /// log10 probability of each known word
+    logp: HashMap<String, f64>,
+    /// log10 probability of an unknown word, before the per-letter penalty
+    unknown: f64,
+    max_len: usize,
+}
+
+impl Segmenter {
+    /// The small built-in list above, weighted by Zipf's law
+    pub fn builtin() -> Self {
+        Self::from_counts(zipf(BUILTIN_WORDS.split_whitespace().map(str::to_string).collect()))
+    }
+
+    /// Read a word list ("word" or "word count" per line, most frequent first)
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let mut words: Vec<String> = Vec::new();
+        let mut counts: Vec<(String, f64)> = Vec::new();
+        for (lineno, line) in text.lines().enumerate() {
+            let mut fields = line.split_whitespace();
+            let Some(word) = fields.next() else { continue };
+            let word = crate::clean_text(word);
+            if word.is_empty() {
+                continue;
+            }
+            match fields.next() {
+                Some(c) => {
+                    let c: f64 = c.parse().map_err(|_| {
+                        io::Error::new(io::ErrorKind::InvalidData, format!("line {}: bad count '{}'", lineno + 1, c))
+                    })?;
+                    counts.push((word, c));
+                }
+                None => words.push(word),
+            }
+        }
+        if !words.is_empty() && !counts.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "mix of lines with and without counts"));
+        }
+        if counts.is_empty() {
+            counts = zipf(words);
+        }
+        if counts.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "word file has no words"));
+        }
+        Ok(Self::from_counts(counts))
+    }
+
+    fn from_counts(counts: Vec<(String, f64)>) -> Self {
+        let total: f64 = counts.iter().map(|(_, c)| c).sum();
+        let mut logp: HashMap<String, f64> = HashMap::new();
+        for (w, c) in counts {
+            // Case-folding can merge entries ("US"/"us"); keep the sum
+            *logp.entry(w).or_default() += c;
+        }
+        let max_len = logp.keys().map(|w| w.len()).max().unwrap_or(0);
+        for v in logp.values_mut() {
+            *v = (*v / total).log10();
+        }
+        let rarest = logp.values().copied().fold(0.0, f64::min);
+        Segmenter { logp, unknown: rarest - 3.0, max_len }
+    }
+
+    fn word_logp(&self, word: &str) -> f64 {
+        match self.logp.get(word) {
+            Some(&p) => p,
+            None => self.unknown - word.len() as f64,
+        }
+    }
+
+    /// Most likely split of uppercase `text` into words
+    pub fn segment<'t>(&self, text: &'t str) -> Vec<&'t str> {
+        let n = text.len();
+        let longest = self.max_len.max(MAX_UNKNOWN);
+        // best[i] = (score of text[..i], start of its last word)
+        let mut best: Vec<(f64, usize)> = vec![(f64::NEG_INFINITY, 0); n + 1];
+        best[0].0 = 0.0;
+        for i in 1..=n {
+            for j in i.saturating_sub(longest)..i {
+                let word = &text[j..i];
+                if i - j > MAX_UNKNOWN && !self.logp.contains_key(word) {
+                    continue;
+                }
+                let s = best[j].0 + self.word_logp(word);
+                if s > best[i].0 {
+                    best[i] = (s, j);
+                }
+            }
+        }
+        let mut words = Vec::new();
+        let mut i = n;
+        while i > 0 {
+            let j = best[i].1;
+            words.push(&text[j..i]);
+            i = j;
+        }
+        words.reverse();
+        words
+    }
+
+    /// Share of letters that fall in dictionary words (1.0 = every word is known)
+    pub fn coverage(&self, words: &[&str]) -> f64 {
+        let total: usize = words.iter().map(|w| w.len()).sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let known: usize = words.iter().filter(|w| self.logp.contains_key(**w)).map(|w| w.len()).sum();
+        known as f64 / total as f64
+    }
+
+    /// Segmented text joined with spaces, and its word coverage
+    pub fn readable(&self, text: &str) -> (String, f64) {
+        let words = self.segment(text);
+        (words.join(" "), self.coverage(&words))
+    }
+}
+
+/// Counts for a rank-ordered list: frequency proportional to 1 / rank
+fn zipf(words: Vec<String>) -> Vec<(String, f64)> {
+    words.into_iter().enumerate().map(|(r, w)| (w, 1.0 / (r + 1) as f64)).collect()
+}
@@ -0,0 +1,177 @@
+// suffix.rs
+// Suffix array + LCP array, used to find every maximal repeat in the ciphertext.
+//
+// A repeat is *maximal* when it cannot be extended to the left or to the right
+// without losing an occurrence. Every repeated n-gram is contained in some maximal
+// repeat at the same positions, and there are at most n of them, whatever their
+// length. Their occurrence lists can still add up to ~n^2/period on periodic text, so
+// only a few are written out; the Kasiski distances come from neighbouring suffixes.
+
+/// Suffix array by prefix doubling with counting sorts: O(n log n).
+/// `sa[r]` is the start of the r-th smallest suffix.
+pub fn suffix_array(s: &[u8]) -> Vec<usize> {
+    let n = s.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    // Round 0: order by first byte
+    let mut sa: Vec<usize> = (0..n).collect();
+    sa.sort_by_key(|&i| s[i]);
+    let mut rank = vec![0usize; n];
+    for r in 1..n {
+        rank[sa[r]] = rank[sa[r - 1]] + usize::from(s[sa[r]] != s[sa[r - 1]]);
+    }
+
+    let mut by_second = vec![0usize; n];
+    let mut next_rank = vec![0usize; n];
+    let mut k = 1;
+    while rank[sa[n - 1]] < n - 1 {
+        // Order by second key (rank of i+k); suffixes shorter than k come first
+        let mut p = 0;
+        for i in (n - k)..n {
+            by_second[p] = i;
+            p += 1;
+        }
+        for &i in sa.iter() {
+            if i >= k {
+                by_second[p] = i - k;
+                p += 1;
+            }
+        }
+
+        // Stable counting sort by first key (rank of i)
+        let classes = rank[sa[n - 1]] + 1;
+        let mut count = vec![0usize; classes + 1];
+        for &r in rank.iter() {
+            count[r + 1] += 1;
+        }
+        for c in 1..=classes {
+            count[c] += count[c - 1];
+        }
+        for &i in by_second.iter() {
+            sa[count[rank[i]]] = i;
+            count[rank[i]] += 1;
+        }
+
+        // New ranks from (rank[i], rank[i+k]) pairs
+        let key = |i: usize| (rank[i], if i + k < n { Some(rank[i + k]) } else { None });
+        next_rank[sa[0]] = 0;
+        for r in 1..n {
+            next_rank[sa[r]] = next_rank[sa[r - 1]] + usize::from(key(sa[r]) != key(sa[r - 1]));
+        }
+        std::mem::swap(&mut rank, &mut next_rank);
+        k *= 2;
+    }
+    sa
+}
+
+/// Kasai's algorithm: `lcp[r]` is the longest common prefix of suffixes `sa[r-1]` and `sa[r]`
+/// (`lcp[0] = 0`). O(n).
+pub fn lcp_array(s: &[u8], sa: &[usize]) -> Vec<usize> {
+    let n = s.len();
+    let mut rank = vec![0usize; n];
+    for (r, &i) in sa.iter().enumerate() {
+        rank[i] = r;
+    }
+    let mut lcp = vec![0usize; n];
+    let mut h = 0usize;
+    for i in 0..n {
+        if rank[i] == 0 {
+            h = 0;
+            continue;
+        }
+        let j = sa[rank[i] - 1];
+        while i + h < n && j + h < n && s[i + h] == s[j + h] {
+            h += 1;
+        }
+        lcp[rank[i]] = h;
+        h = h.saturating_sub(1);
+    }
+    lcp
+}
+
+/// One maximal repeat: its length, how often it occurs and where (sorted, capped).
+pub struct MaximalRepeat {
+    pub len: usize,
+    pub occurrences: usize,
+    pub positions: Vec<usize>,
+}
+
+/// What one pass over the suffix and LCP arrays yields; O(n) in size however
+/// periodic the text is.
+pub struct Repeats {
+    /// Kasiski distances: |sa[r] - sa[r-1]| for each pair of neighbouring suffixes that
+    /// share at least `min_len` letters
+    pub distances: Vec<usize>,
+    /// Number of maximal repeats of length >= `min_len`
+    pub count: usize,
+    /// The `keep` longest maximal repeats (ties in suffix order)
+    pub longest: Vec<MaximalRepeat>,
+}
+
+/// Maximal repeats of length >= `min_len` and their Kasiski distances.
+///
+/// Neighbouring suffixes in the suffix array that share a prefix of `min_len` are two
+/// occurrences of a repeat, and every occurrence of a repeat has such a neighbour, so one
+/// distance per neighbour pair covers all repeats in O(n) (rather than one per pair of
+/// occurrences). The maximal repeats are the LCP intervals, walked bottom-up with a
+/// stack, whose occurrences are also left-diverse; that test is O(1) per interval from
+/// prefix counts, and only the `keep` longest have their positions (at most
+/// `max_positions` of them) written out.
+pub fn repeats(s: &[u8], min_len: usize, keep: usize, max_positions: usize) -> Repeats {
+    let n = s.len();
+    if n < 2 || min_len == 0 {
+        return Repeats { distances: Vec::new(), count: 0, longest: Vec::new() };
+    }
+    let sa = suffix_array(s);
+    let lcp = lcp_array(s, &sa);
+
+    let distances: Vec<usize> = (1..n).filter(|&r| lcp[r] >= min_len).map(|r| sa[r].abs_diff(sa[r - 1])).collect();
+
+    // changes[r]: ranks r' in 1..=r whose left letter differs from that of rank r' - 1
+    // (the suffix at 0 has no left letter, which differs from every letter)
+    let left = |r: usize| if sa[r] == 0 { None } else { Some(s[sa[r] - 1]) };
+    let mut changes = vec![0usize; n];
+    for r in 1..n {
+        changes[r] = changes[r - 1] + usize::from(left(r) != left(r - 1));
+    }
+
+    // Maximal intervals as (length, lb, rb)
+    let mut intervals: Vec<(usize, usize, usize)> = Vec::new();
+    let mut report = |len: usize, lb: usize, rb: usize| {
+        // left-diverse: some neighbouring pair inside the interval differs on the left
+        if len >= min_len && changes[rb] > changes[lb] {
+            intervals.push((len, lb, rb));
+        }
+    };
+
+    // Stack of open intervals: (lcp value, left bound)
+    let mut stack: Vec<(usize, usize)> = vec![(0, 0)];
+    // A trailing 0 closes every interval still open at the end
+    for (r, &cur) in lcp.iter().chain(std::iter::once(&0)).enumerate().skip(1) {
+        let mut lb = r - 1;
+        while cur < stack[stack.len() - 1].0 {
+            let (len, open_lb) = stack.pop().expect("stack bottom has lcp 0");
+            report(len, open_lb, r - 1);
+            lb = open_lb;
+        }
+        if cur > stack[stack.len() - 1].0 {
+            stack.push((cur, lb));
+        }
+    }
+
+    let count = intervals.len();
+    intervals.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let longest = intervals
+        .into_iter()
+        .take(keep)
+        .map(|(len, lb, rb)| {
+            let mut positions = sa[lb..=rb].to_vec();
+            positions.sort_unstable();
+            positions.truncate(max_positions);
+            MaximalRepeat { len, occurrences: rb - lb + 1, positions }
+        })
+        .collect();
+    Repeats { distances, count, longest }
+}
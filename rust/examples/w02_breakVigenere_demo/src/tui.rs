@@ -0,0 +1,258 @@
+// tui.rs
+// Manual cryptanalysis in the terminal. The ciphertext is laid out in rows of one key
+// length, so each key letter owns one column. For the selected column the letter
+// frequencies of its decryption are drawn against English (EN_FREQ): nudge the key letter
+// until the bars line up, watch the plaintext and quadgram score change, lock the
+// columns you trust and let the automatic refinement finish the rest.
+//
+// Keys: Left/Right select column, Up/Down nudge its key letter (or type it, Shift+A-Z),
+// Space lock/unlock, r refine unlocked columns, +/- change key length, q quit.
+
+use std::collections::HashMap;
+use std::io;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::cli::{Cli, TuiArgs};
+use crate::report::print_json;
+use crate::{best_shift_for_column, estimate_period, quadgram_score, vigenere_decrypt, EN_FREQ};
+
+/// Cells in a full frequency bar, which stands for 15% (a little above E)
+const BAR_WIDTH: usize = 30;
+
+struct Session<'a> {
+    cipher: &'a str,
+    quadgrams: &'a HashMap<[u8; 4], f64>,
+    /// Key letters as shifts 0..26, one per column
+    key: Vec<u8>,
+    locked: Vec<bool>,
+    selected: usize,
+    plaintext: String,
+    fitness: f64,
+}
+
+impl<'a> Session<'a> {
+    fn new(cipher: &'a str, quadgrams: &'a HashMap<[u8; 4], f64>, klen: usize) -> Self {
+        let mut s = Session {
+            cipher,
+            quadgrams,
+            key: Vec::new(),
+            locked: Vec::new(),
+            selected: 0,
+            plaintext: String::new(),
+            fitness: 0.0,
+        };
+        s.set_key_len(klen);
+        s
+    }
+
+    /// Start over with a new key length, each column at its chi-squared best shift
+    fn set_key_len(&mut self, klen: usize) {
+        self.key = (0..klen).map(|col| best_shift_for_column(&self.column(col, klen)).0 as u8).collect();
+        self.locked = vec![false; klen];
+        self.selected = self.selected.min(klen - 1);
+        self.update();
+    }
+
+    fn column(&self, col: usize, klen: usize) -> Vec<u8> {
+        self.cipher.bytes().skip(col).step_by(klen).collect()
+    }
+
+    fn key_string(&self) -> String {
+        self.key.iter().map(|&k| (b'A' + k) as char).collect()
+    }
+
+    fn update(&mut self) {
+        self.plaintext = vigenere_decrypt(self.cipher, &self.key_string());
+        self.fitness = quadgram_score(&self.plaintext, self.quadgrams);
+    }
+
+    fn nudge(&mut self, delta: u8) {
+        self.key[self.selected] = (self.key[self.selected] + delta) % 26;
+        self.update();
+    }
+
+    /// Coordinate ascent on quadgram fitness over the unlocked columns: try all 26
+    /// letters in each, keep the best, repeat until nothing improves.
+    fn refine(&mut self) {
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for col in 0..self.key.len() {
+                if self.locked[col] {
+                    continue;
+                }
+                let current = self.key[col];
+                let mut best = (current, self.fitness);
+                for shift in 0..26u8 {
+                    self.key[col] = shift;
+                    let f = quadgram_score(&vigenere_decrypt(self.cipher, &self.key_string()), self.quadgrams);
+                    if f > best.1 {
+                        best = (shift, f);
+                    }
+                }
+                self.key[col] = best.0;
+                if best.0 != current {
+                    improved = true;
+                    self.update();
+                }
+            }
+        }
+        self.update();
+    }
+}
+
+/// Entry point for `tui`.
+pub fn run(cli: &Cli, args: &TuiArgs, cipher: &str, quadgrams: &HashMap<[u8; 4], f64>) {
+    let klen = args.key_len.unwrap_or_else(|| estimate_period(cipher, 20).0).min(cipher.len());
+    let mut session = Session::new(cipher, quadgrams, klen);
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut session);
+    ratatui::restore();
+    if let Err(e) = result {
+        eprintln!("Terminal error: {}", e);
+        std::process::exit(1);
+    }
+
+    // Leave the final state on stdout so the session is not lost
+    if cli.json {
+        print_json(&serde_json::json!({
+            "key": session.key_string(),
+            "fitness": session.fitness,
+            "plaintext": session.plaintext,
+        }));
+    } else {
+        println!("Key: {} (fitness {:.2})", session.key_string(), session.fitness);
+        println!("Plaintext:\n{}", session.plaintext);
+    }
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, s: &mut Session) -> io::Result<()> {
+    loop {
+        terminal.draw(|f| draw(f, s))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let klen = s.key.len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Left => s.selected = (s.selected + klen - 1) % klen,
+            KeyCode::Right => s.selected = (s.selected + 1) % klen,
+            KeyCode::Up => s.nudge(1),
+            KeyCode::Down => s.nudge(25),
+            KeyCode::Char(' ') => s.locked[s.selected] = !s.locked[s.selected],
+            KeyCode::Char('r') => s.refine(),
+            KeyCode::Char('+') if klen < s.cipher.len() => s.set_key_len(klen + 1),
+            KeyCode::Char('-') if klen > 1 => s.set_key_len(klen - 1),
+            KeyCode::Char(c) if c.is_ascii_uppercase() => {
+                s.key[s.selected] = c as u8 - b'A';
+                s.update();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(f: &mut Frame, s: &Session) {
+    let [top, middle, bottom, help] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(8),
+        Constraint::Length(8),
+        Constraint::Length(1),
+    ])
+    .areas(f.area());
+    let [grid, bars] = Layout::horizontal([Constraint::Min(20), Constraint::Length(BAR_WIDTH as u16 + 18)]).areas(middle);
+
+    // Key line: selected letter highlighted, locked letters underlined
+    let mut spans = vec![Span::raw("Key: ")];
+    for (i, &k) in s.key.iter().enumerate() {
+        let mut style = Style::default();
+        if i == s.selected {
+            style = style.fg(Color::Black).bg(Color::Yellow);
+        }
+        if s.locked[i] {
+            style = style.add_modifier(Modifier::UNDERLINED | Modifier::BOLD);
+        }
+        spans.push(Span::styled(((b'A' + k) as char).to_string(), style));
+    }
+    spans.push(Span::raw(format!("   length {}   quadgram fitness {:.2}", s.key.len(), s.fitness)));
+    f.render_widget(Paragraph::new(Line::from(spans)).block(Block::bordered()), top);
+
+    draw_grid(f, s, grid);
+    draw_bars(f, s, bars);
+
+    f.render_widget(
+        Paragraph::new(s.plaintext.as_str()).wrap(Wrap { trim: false }).block(Block::bordered().title("Plaintext")),
+        bottom,
+    );
+    f.render_widget(
+        Paragraph::new("<-/-> column  Up/Down or Shift+A-Z key letter  Space lock  r refine  +/- length  q quit"),
+        help,
+    );
+}
+
+/// Ciphertext in rows of one key length, plaintext beside it, selected column highlighted
+fn draw_grid(f: &mut Frame, s: &Session, area: Rect) {
+    let klen = s.key.len();
+    let rows = (area.height as usize).saturating_sub(2);
+    let lines: Vec<Line> = s
+        .cipher
+        .as_bytes()
+        .chunks(klen)
+        .zip(s.plaintext.as_bytes().chunks(klen))
+        .take(rows)
+        .map(|(c, p)| {
+            let mut spans = Vec::with_capacity(2 * klen + 1);
+            for (half, style) in [(c, Style::default().fg(Color::DarkGray)), (p, Style::default())] {
+                for (i, &b) in half.iter().enumerate() {
+                    let style = if i == s.selected { style.fg(Color::Yellow) } else { style };
+                    spans.push(Span::styled((b as char).to_string(), style));
+                }
+                spans.push(Span::raw(" ".repeat(klen - half.len() + 3)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines).block(Block::bordered().title("Ciphertext | plaintext")), area);
+}
+
+/// Letter frequencies of the selected column's decryption (bars) against English (numbers)
+fn draw_bars(f: &mut Frame, s: &Session, area: Rect) {
+    let col: Vec<u8> = s.plaintext.bytes().skip(s.selected).step_by(s.key.len()).collect();
+    let mut counts = [0usize; 26];
+    for &b in col.iter() {
+        counts[(b - b'A') as usize] += 1;
+    }
+    let n = col.len().max(1) as f64;
+    let lines: Vec<Line> = counts
+        .iter()
+        .zip(EN_FREQ.iter())
+        .enumerate()
+        .map(|(i, (&c, &en))| {
+            let obs = c as f64 / n;
+            let cells = |p: f64| ((p / 0.15) * BAR_WIDTH as f64).round().min(BAR_WIDTH as f64) as usize;
+            let (o, e) = (cells(obs), cells(en));
+            // Observed bar, with the English level marked where the bar falls short of it
+            let bar: String = (0..BAR_WIDTH)
+                .map(|x| if x < o { '█' } else if x + 1 == e { '|' } else { ' ' })
+                .collect();
+            let colour = if (obs - en).abs() < 0.02 { Color::Green } else { Color::Red };
+            Line::from(vec![
+                Span::raw(format!("{} ", (b'A' + i as u8) as char)),
+                Span::styled(bar, Style::default().fg(colour)),
+                Span::raw(format!(" {:4.1} {:4.1}", 100.0 * obs, 100.0 * en)),
+            ])
+        })
+        .collect();
+    let title = format!("Column {} (obs% en%)", s.selected + 1);
+    f.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), area);
+}
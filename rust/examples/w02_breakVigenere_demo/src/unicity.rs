@@ -0,0 +1,181 @@
+// unicity.rs
+// How much ciphertext does it take before only one key makes sense? Shannon's unicity
+// distance
+//
+//   U = log2(number of keys) / D,   D = log2(alphabet) - H   (redundancy per symbol)
+//
+// where H is the entropy of the language per symbol. Below U, several keys give
+// plausible plaintexts and no attack can tell which was used; above it one key is
+// expected to stand out. U says nothing about the work needed to find that key.
+//
+// H comes from the loaded quadgram model: the conditional entropy of the fourth letter
+// given the first three, H(Q4) - H(Q3), with Q3 the table summed over its last letter.
+// A small table knows few quadgrams and so underestimates H (overestimates redundancy).
+//
+// XOR works on raw bytes, spaces, case and punctuation included, so its H is per byte:
+// each byte of the sample is reduced to a class (lower-case letter, upper-case letter, or
+// the byte itself), the class entropy taken from their frequencies, and each letter adds
+// the model's H on top. Classes are counted without context, so this overestimates the
+// byte entropy a little (underestimates the redundancy).
+//
+// The estimate is then put to the test: English samples of about 1/2, 1, 2, 4, 8 and
+// 16 unicity distances are encrypted under random keys and given to this tool's breakers
+// (with the key length known). Real breakers need far more than U, and the table shows
+// by how much.
+
+use std::collections::HashMap;
+use std::fs;
+
+use rand::Rng;
+
+use crate::cli::{Cli, ColumnSolver, UnicityArgs, UnicityCipher};
+use crate::confidence::BUILTIN_REFERENCE;
+use crate::homophonic::climb_codes;
+use crate::identify::break_xor;
+use crate::ngrams::{dense_quadgrams, note_if_builtin};
+use crate::report::{print_json, LanguageEntropy, UnicityReport, UnicityRow, UnicityTrial};
+use crate::{clean_text, try_key_length, vigenere_encrypt, EN_FREQ};
+
+/// Multiples of the unicity distance tried in the validation
+const MULTIPLES: [f64; 6] = [0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+/// Share of plaintext symbols that must come back right for a trial to count as broken
+const SOLVED: f64 = 0.9;
+/// Outer moves of the substitution climb per trial
+const SUBSTITUTION_ITERATIONS: usize = 100;
+
+/// Entry point for `unicity` (reads no ciphertext).
+pub fn run(cli: &Cli, args: &UnicityArgs, quadgrams: &HashMap<[u8; 4], f64>) {
+    let sample = match &args.sample {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("Failed to read sample {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => BUILTIN_REFERENCE.to_string(),
+    };
+    let letters = clean_text(&sample);
+    if letters.len() < 50 {
+        eprintln!("Sample text too short: {} letters", letters.len());
+        std::process::exit(1);
+    }
+
+    let language = entropy(quadgrams, sample.trim().as_bytes());
+    let ciphers: Vec<UnicityCipher> = match args.cipher {
+        Some(c) => vec![c],
+        None => vec![UnicityCipher::Xor, UnicityCipher::Vigenere, UnicityCipher::Substitution],
+    };
+    let dense = dense_quadgrams(quadgrams);
+    let mut rng = rand::thread_rng();
+
+    let rows: Vec<UnicityRow> = ciphers
+        .into_iter()
+        .map(|cipher| {
+            let k = args.key_len;
+            let (name, key_space_bits, alphabet_bits) = match cipher {
+                UnicityCipher::Xor => (format!("repeating-key XOR, {}-byte key", k), 8.0 * k as f64, 8.0),
+                UnicityCipher::Vigenere => (format!("Vigenere, key length {}", k), k as f64 * 26f64.log2(), 26f64.log2()),
+                UnicityCipher::Substitution => ("simple substitution".to_string(), log2_factorial(26), 26f64.log2()),
+            };
+            let symbol_bits = if cipher == UnicityCipher::Xor { language.byte_bits } else { language.model_bits };
+            let redundancy = alphabet_bits - symbol_bits;
+            let unicity_distance = key_space_bits / redundancy;
+            // XOR works on the raw bytes (spaces, case, punctuation), the others on letters
+            let source = if cipher == UnicityCipher::Xor { sample.trim().as_bytes() } else { letters.as_bytes() };
+            let trials = MULTIPLES
+                .iter()
+                .map(|&m| (m, (m * unicity_distance).round().max(4.0) as usize))
+                .filter(|&(_, len)| len <= source.len() && args.trials > 0)
+                .map(|(multiple, length)| {
+                    let solved = (0..args.trials).filter(|_| trial(cipher, k, source, length, &dense, &mut rng)).count();
+                    UnicityTrial { multiple, length, success_rate: solved as f64 / args.trials as f64 }
+                })
+                .collect();
+            UnicityRow { cipher: name, key_space_bits, alphabet_bits, redundancy, unicity_distance, trials }
+        })
+        .collect();
+
+    let report = UnicityReport { quadgrams: quadgrams.len(), language, solved_share: SOLVED, trials_per_length: args.trials, ciphers: rows };
+    if cli.json {
+        print_json(&report);
+        return;
+    }
+    let l = &report.language;
+    println!("Entropy per letter: {:.3} bits from letter frequencies, {:.3} bits from the quadgram model", l.unigram_bits, l.model_bits);
+    println!("Redundancy per letter: {:.3} of {:.3} bits", 26f64.log2() - l.model_bits, 26f64.log2());
+    println!("Entropy per raw byte of the sample (for XOR): {:.3} bits, redundancy {:.3} of 8 bits\n", l.byte_bits, 8.0 - l.byte_bits);
+    for r in report.ciphers.iter() {
+        println!("{}", r.cipher);
+        println!(
+            "  key space 2^{:.1} | redundancy {:.3} bits/symbol | unicity distance {:.1} symbols",
+            r.key_space_bits, r.redundancy, r.unicity_distance
+        );
+        for t in r.trials.iter() {
+            println!("  {:5.1} x U = {:4} symbols: broken in {:3.0}% of {} trials", t.multiple, t.length, 100.0 * t.success_rate, report.trials_per_length);
+        }
+        println!();
+    }
+    println!("A trial counts as broken when {:.0}% of the plaintext comes back right.", 100.0 * SOLVED);
+    note_if_builtin(quadgrams);
+}
+
+/// Entropy of English per letter: from EN_FREQ, and conditional on three letters from the
+/// quadgrams; per byte of `raw` from its byte classes and the quadgram figure per letter
+fn entropy(quadgrams: &HashMap<[u8; 4], f64>, raw: &[u8]) -> LanguageEntropy {
+    let total: f64 = EN_FREQ.iter().sum();
+    let unigram_bits = -EN_FREQ.iter().map(|&f| f / total).map(|p| p * p.log2()).sum::<f64>();
+
+    let probs: Vec<([u8; 4], f64)> = quadgrams.iter().map(|(q, &l)| (*q, 10f64.powf(l))).collect();
+    let sum: f64 = probs.iter().map(|(_, p)| p).sum();
+    let mut trigrams: HashMap<[u8; 3], f64> = HashMap::new();
+    let mut h4 = 0.0;
+    for (q, p) in probs.iter() {
+        let p = p / sum;
+        h4 -= p * p.log2();
+        *trigrams.entry([q[0], q[1], q[2]]).or_insert(0.0) += p;
+    }
+    let h3: f64 = -trigrams.values().map(|&p| p * p.log2()).sum::<f64>();
+    let model_bits = (h4 - h3).max(0.0);
+
+    let mut classes = [0usize; 256];
+    for &b in raw {
+        let class = if b.is_ascii_lowercase() { b'a' } else if b.is_ascii_uppercase() { b'A' } else { b };
+        classes[class as usize] += 1;
+    }
+    let n = raw.len().max(1) as f64;
+    let class_bits = -classes.iter().filter(|&&c| c > 0).map(|&c| c as f64 / n).map(|p| p * p.log2()).sum::<f64>();
+    let letter_share = (classes[b'a' as usize] + classes[b'A' as usize]) as f64 / n;
+    LanguageEntropy { unigram_bits, model_bits, byte_bits: class_bits + letter_share * model_bits }
+}
+
+fn log2_factorial(n: u32) -> f64 {
+    (2..=n).map(|i| (i as f64).log2()).sum()
+}
+
+/// Encrypt a random stretch of `source` under a random key, break it, and check the result
+fn trial<R: Rng>(cipher: UnicityCipher, k: usize, source: &[u8], length: usize, dense: &[f64], rng: &mut R) -> bool {
+    let start = rng.gen_range(0..=source.len() - length);
+    let plain = &source[start..start + length];
+    let recovered: Vec<u8> = match cipher {
+        UnicityCipher::Xor => {
+            let key: Vec<u8> = (0..k).map(|_| rng.gen()).collect();
+            let ct: Vec<u8> = plain.iter().zip(key.iter().cycle()).map(|(p, k)| p ^ k).collect();
+            break_xor(&ct, k).1
+        }
+        UnicityCipher::Vigenere => {
+            let key: String = (0..k).map(|_| (b'A' + rng.gen_range(0..26)) as char).collect();
+            let ct = vigenere_encrypt(std::str::from_utf8(plain).expect("letters"), &key);
+            try_key_length(&ct, k, ColumnSolver::Chi).2.into_bytes()
+        }
+        UnicityCipher::Substitution => {
+            let mut alphabet: Vec<u8> = (0..26).collect();
+            rand::seq::SliceRandom::shuffle(alphabet.as_mut_slice(), rng);
+            let ct: Vec<usize> = plain.iter().map(|&p| alphabet[(p - b'A') as usize] as usize).collect();
+            let (letters, _) = climb_codes(&ct, 26, dense, SUBSTITUTION_ITERATIONS, rng);
+            ct.iter().map(|&c| b'A' + letters[c]).collect()
+        }
+    };
+    let right = plain.iter().zip(recovered.iter()).filter(|(a, b)| a == b).count();
+    right as f64 >= SOLVED * length as f64
+}
@@ -0,0 +1,221 @@
+// Nonlinear combiners of LFSRs, and Siegenthaler's correlation attack on them.
+//
+// Combining several LFSRs through a nonlinear function f raises the linear complexity
+// beyond the reach of Berlekamp-Massey, and the key is all the initial states together:
+// 2^(L1 + L2 + L3) for three registers. But if the keystream agrees with one register's
+// output more often than half the time, that register can be attacked on its own
+// (Siegenthaler 1985): try each of its 2^Li states and keep the one whose output agrees
+// most with the keystream. The cost is 2^L1 + 2^L2 + 2^L3, not their product.
+//
+// - Geffe: z = x1 x2 + (1 + x1) x3, a multiplexer. z = x2 and z = x3 each with
+//   probability 3/4, so registers 2 and 3 fall to correlation. Register 1 is
+//   uncorrelated, but once x2 and x3 are known, z = x1's choice wherever x2 != x3.
+// - Threshold (majority for three registers): z = 1 when more than half the inputs are
+//   1. Every input agrees with z with probability 1/2 + C(k-1, (k-1)/2) / 2^k.
+//
+// Keystream needed: the right state agrees about N p times, the best of the 2^L wrong
+// ones about N/2 + sqrt(N/4) sqrt(2 L ln 2). Asking the first to beat the second by three
+// standard deviations gives
+//
+//   sqrt(N) >= (sqrt(2 L ln 2) / 2 + 3 sqrt(p (1 - p))) / (p - 1/2)
+//
+// The search itself uses linearity: a register's output is the XOR of the outputs of its
+// set state bits, so walking the states in Gray code order costs one XOR of packed
+// sequences and a popcount per state.
+
+use std::time::Instant;
+
+use clap::{Args, ValueEnum};
+
+use crate::lfsr::{Layout, Lfsr};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Combiner {
+    /// Geffe generator: register 1 selects register 2 or 3
+    Geffe,
+    /// Majority of three registers
+    Majority,
+    /// Threshold generator: majority of five registers
+    Threshold5,
+}
+
+/// Primitive connection polynomials (exponents) for the component registers
+const REGISTERS: [&[u32]; 5] = [&[17, 14], &[19, 6, 2, 1], &[23, 18], &[13, 4, 3, 1], &[11, 9]];
+
+#[derive(Args, Debug)]
+pub struct CorrelationArgs {
+    /// How the registers are combined
+    #[arg(long, value_enum, default_value_t = Combiner::Geffe)]
+    combiner: Combiner,
+
+    /// Keystream bits the attacker observes (default: the estimate for the longest register)
+    #[arg(long, value_name = "N")]
+    keystream: Option<usize>,
+}
+
+/// Registers and a combining function
+pub struct CombinedGenerator {
+    combiner: Combiner,
+    registers: Vec<Lfsr>,
+}
+
+impl CombinedGenerator {
+    pub fn new(combiner: Combiner, states: &[u64]) -> Self {
+        let count = if combiner == Combiner::Threshold5 { 5 } else { 3 };
+        let registers = REGISTERS[..count].iter().zip(states).map(|(taps, &s)| Lfsr::new(Layout::Galois, taps, s)).collect();
+        Self { combiner, registers }
+    }
+
+    pub fn next_bit(&mut self) -> u8 {
+        let x: Vec<u8> = self.registers.iter_mut().map(|r| r.next_bit()).collect();
+        match self.combiner {
+            Combiner::Geffe => (x[0] & x[1]) ^ ((1 ^ x[0]) & x[2]),
+            Combiner::Majority | Combiner::Threshold5 => (2 * x.iter().filter(|&&b| b == 1).count() > x.len()) as u8,
+        }
+    }
+
+    pub fn bits(&mut self, n: usize) -> Vec<u8> {
+        (0..n).map(|_| self.next_bit()).collect()
+    }
+}
+
+/// Probability that the output equals register `i`'s bit
+fn correlation(combiner: Combiner, i: usize) -> f64 {
+    match (combiner, i) {
+        (Combiner::Geffe, 0) => 0.5,
+        (Combiner::Geffe, _) => 0.75,
+        (Combiner::Majority, _) => 0.75,
+        // 1/2 + C(4, 2) / 2^5
+        (Combiner::Threshold5, _) => 0.5 + 6.0 / 32.0,
+    }
+}
+
+/// Keystream bits for the right state of a length-L register to stand out (see the top)
+fn needed(len: u32, p: f64) -> usize {
+    let spread = (2.0 * len as f64 * std::f64::consts::LN_2).sqrt() / 2.0 + 3.0 * (p * (1.0 - p)).sqrt();
+    (spread / (p - 0.5)).powi(2).ceil() as usize
+}
+
+fn pack(bits: &[u8]) -> Vec<u64> {
+    bits.chunks(64).map(|c| c.iter().enumerate().fold(0u64, |w, (i, &b)| w | ((b as u64) << i))).collect()
+}
+
+/// Result of searching one register
+struct Search {
+    state: u64,
+    agree: u32,
+    runner_up: u32,
+}
+
+/// State of the register with `taps` whose output agrees with `keystream` on the most
+/// positions in `mask`, over all nonzero states in Gray code order
+fn search(taps: &[u32], keystream: &[u64], mask: &[u64], n: usize) -> Search {
+    let len = *taps.iter().max().expect("taps");
+    let basis: Vec<Vec<u64>> = (0..len).map(|i| pack(&Lfsr::new(Layout::Galois, taps, 1 << i).bits(n))).collect();
+    let mut current = vec![0u64; keystream.len()];
+    let (mut state, mut best) = (0u64, Search { state: 0, agree: 0, runner_up: 0 });
+    for i in 1u64..(1 << len) {
+        let bit = i.trailing_zeros() as usize;
+        state ^= 1 << bit;
+        for (c, b) in current.iter_mut().zip(basis[bit].iter()) {
+            *c ^= b;
+        }
+        let agree: u32 = current.iter().zip(keystream).zip(mask).map(|((c, k), m)| (!(c ^ k) & m).count_ones()).sum();
+        if agree > best.agree {
+            best = Search { state, agree, runner_up: best.agree };
+        } else if agree > best.runner_up {
+            best.runner_up = agree;
+        }
+    }
+    best
+}
+
+pub fn run(args: &CorrelationArgs) {
+    let count = if args.combiner == Combiner::Threshold5 { 5 } else { 3 };
+    let states: Vec<u64> = (0..count).map(|_| rand::random()).collect();
+    let mut victim = CombinedGenerator::new(args.combiner, &states);
+    let truth: Vec<u64> = victim.registers.iter().map(|r| r.state()).collect();
+    let lens: Vec<u32> = victim.registers.iter().map(|r| r.len()).collect();
+    let key_bits: u32 = lens.iter().sum();
+    println!("Combiner: {:?}, register lengths {:?}, key space 2^{}", args.combiner, lens, key_bits);
+
+    let estimates: Vec<Option<usize>> = (0..count)
+        .map(|i| {
+            let p = correlation(args.combiner, i);
+            (p > 0.5).then(|| needed(lens[i], p))
+        })
+        .collect();
+    let n = args.keystream.unwrap_or_else(|| estimates.iter().flatten().copied().max().unwrap_or(256));
+    let keystream = victim.bits(n);
+    let packed = pack(&keystream);
+    let full_mask = pack(&vec![1u8; n]);
+    println!("Keystream: {} bits observed\n", n);
+
+    let total = Instant::now();
+    let mut recovered = vec![0u64; count];
+    // Correlated registers first, each on its own
+    for i in (0..count).filter(|&i| estimates[i].is_some()) {
+        let p = correlation(args.combiner, i);
+        let start = Instant::now();
+        let s = search(REGISTERS[i], &packed, &full_mask, n);
+        recovered[i] = s.state;
+        println!(
+            "Register {} (L = {}, P(z = x) = {:.4}, needs ~{} bits): 2^{} states in {:.2?}",
+            i + 1,
+            lens[i],
+            p,
+            estimates[i].unwrap_or(0),
+            lens[i],
+            start.elapsed()
+        );
+        println!(
+            "  best agreement {:.3} (runner-up {:.3}), state {:#x} {}",
+            s.agree as f64 / n as f64,
+            s.runner_up as f64 / n as f64,
+            s.state,
+            verdict(s.state == truth[i])
+        );
+    }
+    // Geffe: register 1 is the selector; where x2 != x3, z shows which one it picked
+    if args.combiner == Combiner::Geffe {
+        let x2 = Lfsr::new(Layout::Galois, REGISTERS[1], recovered[1]).bits(n);
+        let x3 = Lfsr::new(Layout::Galois, REGISTERS[2], recovered[2]).bits(n);
+        // on those positions x1 = 1 exactly when z = x2
+        let selector: Vec<u8> = keystream.iter().zip(&x2).map(|(z, a)| 1 ^ z ^ a).collect();
+        let differ: Vec<u8> = x2.iter().zip(&x3).map(|(a, b)| a ^ b).collect();
+        let start = Instant::now();
+        let s = search(REGISTERS[0], &pack(&selector), &pack(&differ), n);
+        recovered[0] = s.state;
+        let positions = differ.iter().filter(|&&d| d == 1).count();
+        println!(
+            "Register 1 (L = {}, uncorrelated): from the {} positions where x2 != x3, 2^{} states in {:.2?}",
+            lens[0],
+            positions,
+            lens[0],
+            start.elapsed()
+        );
+        println!(
+            "  agreement {}/{} (runner-up {}), state {:#x} {}",
+            s.agree,
+            positions,
+            s.runner_up,
+            s.state,
+            verdict(s.state == truth[0])
+        );
+    }
+
+    let work: f64 = lens.iter().map(|&l| 2f64.powi(l as i32)).sum();
+    println!("\nTotal {:.2?} for about 2^{:.1} register states instead of 2^{}", total.elapsed(), work.log2(), key_bits);
+    let mut clone = CombinedGenerator::new(args.combiner, &recovered);
+    let replayed = clone.bits(n);
+    let ahead = clone.bits(256) == victim.bits(256);
+    println!("Recovered key reproduces the keystream: {}, and predicts the next 256 bits: {}", replayed == keystream, ahead);
+}
+
+fn verdict(right: bool) -> &'static str {
+    if right {
+        "(correct)"
+    } else {
+        "(WRONG: more keystream needed)"
+    }
+}
@@ -0,0 +1,102 @@
+// LLL lattice basis reduction in exact integer arithmetic.
+//
+// The lattices in these attacks have entries around 2^64 and larger, far beyond what
+// f64 Gram-Schmidt can hold exactly, so this is the integral version (de Weger; Cohen,
+// "A Course in Computational Algebraic Number Theory", Alg. 2.6.7). Instead of the
+// rational Gram-Schmidt coefficients mu it keeps the integers
+//
+//   d_i      = det of the Gram matrix of b_1..b_i
+//   lambda_ij = d_j * mu_ij
+//
+// and every division in the update formulas is exact.
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, Zero};
+
+/// Lovasz condition delta = NUM / DEN
+const DELTA_NUM: i64 = 99;
+const DELTA_DEN: i64 = 100;
+
+fn dot(a: &[BigInt], b: &[BigInt]) -> BigInt {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// LLL-reduce the rows of `basis` in place. Rows must be linearly independent.
+pub fn lll(basis: &mut [Vec<BigInt>]) {
+    let n = basis.len();
+    if n < 2 {
+        return;
+    }
+    // 1-based as in the reference: d[0] = 1, d[i] for b_1..b_i; lambda[k][j] for j < k
+    let mut d = vec![BigInt::zero(); n + 1];
+    let mut lambda = vec![vec![BigInt::zero(); n + 1]; n + 1];
+    d[0] = BigInt::from(1);
+    d[1] = dot(&basis[0], &basis[0]);
+    let (mut k, mut k_max) = (2, 1);
+
+    while k <= n {
+        if k > k_max {
+            // Incremental Gram-Schmidt for b_k
+            k_max = k;
+            for j in 1..=k {
+                let mut u = dot(&basis[k - 1], &basis[j - 1]);
+                for i in 1..j {
+                    u = (&d[i] * &u - &lambda[k][i] * &lambda[j][i]) / &d[i - 1];
+                }
+                if j < k {
+                    lambda[k][j] = u;
+                } else {
+                    assert!(!u.is_zero(), "LLL basis rows are linearly dependent");
+                    d[k] = u;
+                }
+            }
+        }
+        reduce(basis, &mut lambda, &d, k, k - 1);
+        // Lovasz condition in integers: swap if d_k d_{k-2} < delta d_{k-1}^2 - lambda^2
+        let lhs = &d[k] * &d[k - 2] * DELTA_DEN;
+        let rhs = (&d[k - 1] * &d[k - 1] * DELTA_NUM) - (&lambda[k][k - 1] * &lambda[k][k - 1] * DELTA_DEN);
+        if lhs < rhs {
+            swap(basis, &mut lambda, &mut d, k, k_max);
+            k = (k - 1).max(2);
+        } else {
+            for l in (1..k - 1).rev() {
+                reduce(basis, &mut lambda, &d, k, l);
+            }
+            k += 1;
+        }
+    }
+}
+
+/// Size-reduce b_k against b_l
+fn reduce(basis: &mut [Vec<BigInt>], lambda: &mut [Vec<BigInt>], d: &[BigInt], k: usize, l: usize) {
+    if (&lambda[k][l] * 2i32).abs() <= d[l] {
+        return;
+    }
+    // q = nearest integer to lambda_kl / d_l
+    let q = (&lambda[k][l] * 2i32 + &d[l]).div_floor(&(&d[l] * 2i32));
+    let bl = basis[l - 1].clone();
+    for (x, y) in basis[k - 1].iter_mut().zip(bl.iter()) {
+        *x -= &q * y;
+    }
+    lambda[k][l] -= &q * &d[l];
+    let row_l = lambda[l][1..l].to_vec();
+    for (x, y) in lambda[k][1..l].iter_mut().zip(row_l.iter()) {
+        *x -= &q * y;
+    }
+}
+
+/// Exchange b_k and b_{k-1} and update d and lambda
+fn swap(basis: &mut [Vec<BigInt>], lambda: &mut [Vec<BigInt>], d: &mut [BigInt], k: usize, k_max: usize) {
+    basis.swap(k - 1, k - 2);
+    let (lower, upper) = lambda.split_at_mut(k);
+    lower[k - 1][1..k - 1].swap_with_slice(&mut upper[0][1..k - 1]);
+    let lam = lambda[k][k - 1].clone();
+    let b = (&d[k - 2] * &d[k] + &lam * &lam) / &d[k - 1];
+    for row in lambda[k + 1..=k_max].iter_mut() {
+        let t = row[k].clone();
+        row[k] = (&d[k] * &row[k - 1] - &lam * &t) / &d[k - 1];
+        row[k - 1] = (&b * &t + &lam * &row[k]) / &d[k];
+    }
+    d[k - 1] = b;
+}
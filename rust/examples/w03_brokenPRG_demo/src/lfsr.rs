@@ -0,0 +1,219 @@
+// Linear feedback shift registers, and Berlekamp-Massey to synthesise them from output.
+//
+// An LFSR of length L outputs bits satisfying
+//
+//   s_n = c_1 s_{n-1} + c_2 s_{n-2} + ... + c_L s_{n-L}   (mod 2)
+//
+// with connection polynomial C(x) = 1 + c_1 x + ... + c_L x^L. Good taps (a primitive C)
+// give period 2^L - 1 and fine statistics, but every output bit is a linear function of
+// the initial state - the same weakness as the linear `toy_crc` of w05. Berlekamp-Massey
+// finds the shortest LFSR that generates a sequence in O(n^2) bit operations, and 2L
+// bits are enough to pin down C and the state. Then every later bit is known.
+//
+// Both layouts are here: Fibonacci (feedback from several cells into one) and Galois
+// (one output bit toggled into several cells). With the same taps they generate the
+// same kind of sequence, and Berlekamp-Massey recovers C from either.
+
+use std::time::Instant;
+
+use clap::{Args, ValueEnum};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Layout {
+    Fibonacci,
+    Galois,
+}
+
+/// A binary LFSR of length 1..=64
+#[derive(Clone, Debug)]
+pub struct Lfsr {
+    layout: Layout,
+    len: u32,
+    /// Fibonacci: cells summed into the new bit. Galois: cells toggled by the output bit.
+    mask: u64,
+    state: u64,
+}
+
+/// Mask of the low `len` bits
+fn low_bits(len: u32) -> u64 {
+    if len >= 64 { u64::MAX } else { (1 << len) - 1 }
+}
+
+impl Lfsr {
+    /// LFSR with C(x) = 1 + sum of x^t over `taps` (the largest tap is the length).
+    /// A zero state is replaced by 1: the all-zero register never leaves zero.
+    pub fn new(layout: Layout, taps: &[u32], state: u64) -> Self {
+        let len = taps.iter().copied().max().expect("at least one tap");
+        assert!((1..=64).contains(&len) && !taps.contains(&0), "taps must be in 1..=64");
+        // Fibonacci: bit j holds s_{n+j}, so s_{n+L-t} is bit L - t.
+        // Galois: the output toggles cell t - 1 for each tap t.
+        let mask = taps.iter().fold(0u64, |m, &t| m | (1 << if layout == Layout::Fibonacci { len - t } else { t - 1 }));
+        let state = match state & low_bits(len) {
+            0 => 1,
+            s => s,
+        };
+        Self { layout, len, mask, state }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_bit(&mut self) -> u8 {
+        let out = (self.state & 1) as u8;
+        match self.layout {
+            Layout::Fibonacci => {
+                let feedback = (self.state & self.mask).count_ones() as u64 & 1;
+                self.state = (self.state >> 1) | (feedback << (self.len - 1));
+            }
+            Layout::Galois => {
+                self.state >>= 1;
+                if out == 1 {
+                    self.state ^= self.mask;
+                }
+            }
+        }
+        out
+    }
+
+    pub fn bits(&mut self, n: usize) -> Vec<u8> {
+        (0..n).map(|_| self.next_bit()).collect()
+    }
+}
+
+/// Shortest LFSR generating `s`: its length L and connection polynomial coefficients
+/// c_0 = 1, c_1, ..., c_L (Massey 1969)
+pub fn berlekamp_massey(s: &[u8]) -> (usize, Vec<u8>) {
+    let n = s.len();
+    let mut c = vec![0u8; n + 1];
+    let mut b = vec![0u8; n + 1];
+    c[0] = 1;
+    b[0] = 1;
+    let (mut l, mut m) = (0usize, 1usize);
+    for i in 0..n {
+        // discrepancy: does the current LFSR predict s_i?
+        let d = (1..=l).fold(s[i], |d, j| d ^ (c[j] & s[i - j]));
+        if d == 0 {
+            m += 1;
+            continue;
+        }
+        let previous = c.clone();
+        for j in 0..=n - m {
+            c[j + m] ^= b[j];
+        }
+        if 2 * l <= i {
+            l = i + 1 - l;
+            b = previous;
+            m = 1;
+        } else {
+            m += 1;
+        }
+    }
+    c.truncate(l + 1);
+    (l, c)
+}
+
+/// Continue a sequence from its last L bits with connection polynomial `c`
+pub fn extend(s: &[u8], c: &[u8], n: usize) -> Vec<u8> {
+    let l = c.len() - 1;
+    let mut all = s.to_vec();
+    for _ in 0..n {
+        let k = all.len();
+        let bit = (1..=l).fold(0, |acc, j| acc ^ (c[j] & all[k - j]));
+        all.push(bit);
+    }
+    all.split_off(s.len())
+}
+
+/// "x^32 + x^22 + x^2 + x + 1" for coefficients c_0..c_L
+pub fn poly_string(c: &[u8]) -> String {
+    let terms: Vec<String> = c
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, &b)| b == 1)
+        .map(|(i, _)| match i {
+            0 => "1".to_string(),
+            1 => "x".to_string(),
+            i => format!("x^{}", i),
+        })
+        .collect();
+    terms.join(" + ")
+}
+
+pub fn bit_string(bits: &[u8]) -> String {
+    bits.iter().map(|&b| (b'0' + b) as char).collect()
+}
+
+#[derive(Args, Debug)]
+pub struct LfsrArgs {
+    /// Register layout of the victim
+    #[arg(long, value_enum, default_value_t = Layout::Galois)]
+    layout: Layout,
+
+    /// Exponents of C(x) besides x^0; the largest is the length (default: a primitive
+    /// polynomial, x^32 + x^22 + x^2 + x + 1)
+    #[arg(long, value_name = "T1,T2,...", value_delimiter = ',', default_values_t = [32, 22, 2, 1])]
+    taps: Vec<u32>,
+
+    /// Initial state in hex (default: random)
+    #[arg(long, value_name = "HEX")]
+    seed: Option<String>,
+
+    /// Output bits the attacker observes (default: 2L)
+    #[arg(long, value_name = "N")]
+    observe: Option<usize>,
+
+    /// Bits to predict after synthesis
+    #[arg(long, value_name = "N", default_value_t = 128)]
+    predict: usize,
+}
+
+pub fn run(args: &LfsrArgs) {
+    if args.taps.iter().any(|&t| t == 0 || t > 64) {
+        eprintln!("--taps must be in 1..=64");
+        std::process::exit(1);
+    }
+    let seed = match &args.seed {
+        Some(h) => u64::from_str_radix(h.trim().trim_start_matches("0x"), 16).unwrap_or_else(|_| {
+            eprintln!("--seed must be hex");
+            std::process::exit(1);
+        }),
+        None => rand::random(),
+    };
+    let mut victim = Lfsr::new(args.layout, &args.taps, seed);
+    let len = victim.len() as usize;
+    let mut taps = args.taps.clone();
+    taps.sort_unstable_by(|a, b| b.cmp(a));
+    taps.dedup();
+    let mut c = vec![0u8; len + 1];
+    c[0] = 1;
+    taps.iter().for_each(|&t| c[t as usize] = 1);
+    println!("Victim: {:?} LFSR of length {}, C(x) = {}, state {:#x}", args.layout, len, poly_string(&c), victim.state);
+
+    let n = args.observe.unwrap_or(2 * len);
+    let observed = victim.bits(n);
+    println!("Observed {} bits: {}", n, bit_string(&observed));
+
+    let start = Instant::now();
+    let (l, recovered) = berlekamp_massey(&observed);
+    println!("Berlekamp-Massey in {:.2?}: linear complexity {}", start.elapsed(), l);
+    println!("  C(x) = {}", poly_string(&recovered));
+    // In Fibonacci form the state is the last L bits seen; continue from there
+    println!("  state (last {} bits seen): {}", l, bit_string(&observed[n - l..]));
+
+    let predicted = extend(&observed, &recovered, args.predict);
+    let actual = victim.bits(args.predict);
+    println!("Predicted next {} bits: {}", args.predict, bit_string(&predicted));
+    println!("Victim's next {} bits:  {}", args.predict, bit_string(&actual));
+    let wrong = predicted.iter().zip(actual.iter()).filter(|(a, b)| a != b).count();
+    if wrong == 0 {
+        println!("All predictions match.");
+    } else {
+        println!("{} of {} predicted bits are wrong: {} bits are too few to pin down this register (2L = {}).", wrong, args.predict, n, 2 * len);
+    }
+}
@@ -1,5 +1,16 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use clap::{Parser, Subcommand};
+use num_bigint::BigInt;
+
+mod correlation;
+mod lattice;
+mod lfsr;
+mod mt;
+mod params;
+mod timeseed;
+mod truncated;
+
 /// BAD: 32-bit LCG with parameters from Numerical Recipes.
 /// State is only 32 bits and equals the last output; trivially predictable.
 struct Lcg32 {
@@ -10,6 +21,11 @@ impl Lcg32 {
     fn seeded_from_time() -> Self {
         // BAD: seeding from time has very low entropy (guessable within seconds)
         let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        Self::from_time(secs)
+    }
+
+    /// The seeding of `seeded_from_time` for a given Unix time, as an attacker replays it
+    fn from_time(secs: u64) -> Self {
         Self { state: (secs as u32) ^ 0x9E3779B9 } // arbitrary xor to "mix" (still bad)
     }
 
@@ -33,7 +49,181 @@ impl Lcg32 {
     }
 }
 
+/// BAD: Mersenne Twister MT19937 (Matsumoto & Nishimura), the default of Python's
+/// `random`, C++'s `std::mt19937` and many more. Great statistics, period 2^19937 - 1,
+/// but the output is an invertible function (tempering) of the state words, so 624
+/// outputs give the whole state.
+struct Mt19937 {
+    mt: [u32; Mt19937::N],
+    index: usize,
+}
+
+impl Mt19937 {
+    const N: usize = 624;
+    const M: usize = 397;
+    const MATRIX_A: u32 = 0x9908b0df;
+    const UPPER: u32 = 0x8000_0000;
+    const LOWER: u32 = 0x7fff_ffff;
+
+    /// Reference initialisation (`init_genrand`)
+    fn new(seed: u32) -> Self {
+        let mut mt = [0u32; Self::N];
+        mt[0] = seed;
+        for i in 1..Self::N {
+            mt[i] = 1812433253u32.wrapping_mul(mt[i - 1] ^ (mt[i - 1] >> 30)).wrapping_add(i as u32);
+        }
+        Self { mt, index: Self::N }
+    }
+
+    fn seeded_from_time() -> Self {
+        // BAD: the same low-entropy time seed as `Lcg32::seeded_from_time`
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        Self::from_time(secs)
+    }
+
+    /// The seeding of `seeded_from_time` for a given Unix time
+    fn from_time(secs: u64) -> Self {
+        Self::new((secs as u32) ^ 0x9E3779B9)
+    }
+
+    /// Generator whose next outputs continue from a full state (as recovered by an attacker)
+    fn from_state(mt: [u32; Self::N]) -> Self {
+        Self { mt, index: Self::N }
+    }
+
+    /// Regenerate all N state words: x_{k+N} = x_{k+M} ^ A(upper(x_k) | lower(x_{k+1}))
+    fn twist(&mut self) {
+        for i in 0..Self::N {
+            let y = (self.mt[i] & Self::UPPER) | (self.mt[(i + 1) % Self::N] & Self::LOWER);
+            let mag = if y & 1 == 1 { Self::MATRIX_A } else { 0 };
+            self.mt[i] = self.mt[(i + Self::M) % Self::N] ^ (y >> 1) ^ mag;
+        }
+        self.index = 0;
+    }
+
+    /// Tempering: a bijection on u32 that improves equidistribution, hides nothing
+    fn temper(mut y: u32) -> u32 {
+        y ^= y >> 11;
+        y ^= (y << 7) & 0x9d2c5680;
+        y ^= (y << 15) & 0xefc60000;
+        y ^ (y >> 18)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        if self.index >= Self::N {
+            self.twist();
+        }
+        let y = self.mt[self.index];
+        self.index += 1;
+        Self::temper(y)
+    }
+}
+
+/// LCG mod 2^32 that outputs only bits `shift..shift + bits` of the state, as many
+/// C library `rand()`s do. The low bits stay hidden, but there are only 2^(32 - bits)
+/// states behind any one output.
+#[derive(Clone, Copy)]
+struct TruncLcg32 {
+    state: u32,
+    a: u32,
+    c: u32,
+    shift: u32,
+    bits: u32,
+}
+
+impl TruncLcg32 {
+    /// Numerical Recipes constants (as `Lcg32`), top 16 bits out
+    fn nr_high16(state: u32) -> Self {
+        Self { state, a: 1664525, c: 1013904223, shift: 16, bits: 16 }
+    }
+
+    /// Microsoft C runtime `rand()`: bits 16..31, so 15 bits out
+    fn msvc(state: u32) -> Self {
+        Self { state, a: 214013, c: 2531011, shift: 16, bits: 15 }
+    }
+
+    fn output(&self, state: u32) -> u32 {
+        (state >> self.shift) & ((1 << self.bits) - 1)
+    }
+
+    fn next(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(self.a).wrapping_add(self.c);
+        self.output(self.state)
+    }
+}
+
+/// LCG mod 2^64 with Knuth's MMIX constants that outputs the top 32 bits. 2^32 states
+/// fit each output, too many to try for every output, so the attack uses a lattice.
+struct TruncLcg64 {
+    state: u64,
+}
+
+impl TruncLcg64 {
+    const A: u64 = 6364136223846793005;
+    const C: u64 = 1442695040888963407;
+
+    fn next(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(Self::A).wrapping_add(Self::C);
+        (self.state >> 32) as u32
+    }
+}
+
+/// LCG with any modulus, multiplier and increment, on big integers. Like `Lcg32` it
+/// outputs its whole state.
+struct Lcg {
+    m: BigInt,
+    a: BigInt,
+    c: BigInt,
+    state: BigInt,
+}
+
+impl Lcg {
+    fn next(&mut self) -> BigInt {
+        self.state = (&self.a * &self.state + &self.c) % &self.m;
+        self.state.clone()
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "w03_brokenPRG_demo", about = "Why an LCG is not a cryptographic PRG")]
+struct Cli {
+    /// Without a subcommand: predict the next output from one raw output
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Recover the time seed behind a token made by `Lcg32::generate`
+    TimeSeed(timeseed::TimeSeedArgs),
+    /// Recover the state of an LCG that outputs only its high bits
+    Truncated(truncated::TruncatedArgs),
+    /// Recover an LCG's unknown modulus, multiplier and increment from its outputs
+    Params(params::ParamsArgs),
+    /// Clone MT19937 from 624 consecutive outputs by inverting the tempering
+    MtClone(mt::CloneArgs),
+    /// Recover the time seed of MT19937 from one of its first outputs
+    MtTimeSeed(mt::TimeSeedArgs),
+    /// Synthesise an LFSR from 2L output bits with Berlekamp-Massey and predict it
+    Lfsr(lfsr::LfsrArgs),
+    /// Recover the registers of a Geffe or threshold generator one at a time by correlation
+    Correlation(correlation::CorrelationArgs),
+}
+
 fn main() {
+    match Cli::parse().command {
+        None => predict_demo(),
+        Some(Command::TimeSeed(args)) => timeseed::run(&args),
+        Some(Command::Truncated(args)) => truncated::run(&args),
+        Some(Command::Params(args)) => params::run(&args),
+        Some(Command::MtClone(args)) => mt::run_clone(&args),
+        Some(Command::MtTimeSeed(args)) => mt::run_time_seed(&args),
+        Some(Command::Lfsr(args)) => lfsr::run(&args),
+        Some(Command::Correlation(args)) => correlation::run(&args),
+    }
+}
+
+fn predict_demo() {
     // Demonstrate predictability
     let mut prg = Lcg32::seeded_from_time();
     let first = prg.next_u32();
@@ -48,3 +238,8 @@ fn main() {
     let attacker_second = attacker.next_u32();
     println!("Attacker predicts second: {:#010x} (matches)", attacker_second);
 }
+
+/// Current Unix time in seconds
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
@@ -0,0 +1,143 @@
+// Attacks on MT19937: cloning from 624 outputs, and recovering a time seed.
+//
+// Each output is temper(x) for one state word x, and tempering is a bijection built
+// from shifts, masks and XORs, each step of which can be undone (`untemper`). The
+// generator's next word depends only on the previous 624:
+//
+//   x_{k+624} = x_{k+397} ^ A(upper bit of x_k | lower 31 bits of x_{k+1})
+//
+// so 624 consecutive outputs, untempered, are a complete state from whatever point the
+// attacker started listening. Every later output follows.
+//
+// The seed attack mirrors `timeseed` for `Lcg32`: a time seed leaves only the seconds
+// of the window to try, and for each one the generator is run up to `max_skip` outputs.
+
+use std::time::Instant;
+
+use clap::Args;
+use rand::Rng;
+
+use crate::{now_secs, Mt19937};
+
+#[derive(Args, Debug)]
+pub struct CloneArgs {
+    /// Outputs the victim produces before the attacker starts listening (default: random)
+    #[arg(long, value_name = "N")]
+    skip: Option<usize>,
+
+    /// Outputs to predict after cloning
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    predict: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct TimeSeedArgs {
+    /// Observed output (decimal; default: seed a victim from the time now and take its output)
+    #[arg(long, value_name = "U32")]
+    output: Option<u32>,
+
+    /// Search this many seconds back from now
+    #[arg(long, value_name = "SECS", default_value_t = 86_400)]
+    window: u64,
+
+    /// Outputs the generator may have produced before the observed one
+    #[arg(long, value_name = "N", default_value_t = 8)]
+    max_skip: usize,
+}
+
+/// Undo y ^= (y >> shift): the top `shift` bits are already right, and each pass fixes
+/// `shift` more
+fn undo_right(y: u32, shift: u32) -> u32 {
+    (0..32 / shift).fold(y, |x, _| y ^ (x >> shift))
+}
+
+/// Undo y ^= (y << shift) & mask, fixing `shift` more low bits each pass
+fn undo_left(y: u32, shift: u32, mask: u32) -> u32 {
+    (0..32 / shift).fold(y, |x, _| y ^ ((x << shift) & mask))
+}
+
+/// The state word behind an output
+pub fn untemper(y: u32) -> u32 {
+    let y = undo_right(y, 18);
+    let y = undo_left(y, 15, 0xefc60000);
+    let y = undo_left(y, 7, 0x9d2c5680);
+    undo_right(y, 11)
+}
+
+/// A generator that continues exactly where 624 consecutive outputs left off
+pub fn clone_from_outputs(outputs: &[u32]) -> Mt19937 {
+    let mut state = [0u32; Mt19937::N];
+    for (s, &y) in state.iter_mut().zip(outputs) {
+        *s = untemper(y);
+    }
+    Mt19937::from_state(state)
+}
+
+pub fn run_clone(args: &CloneArgs) {
+    // The reference implementation's first output for its default seed
+    assert_eq!(Mt19937::new(5489).next_u32(), 3499211612, "MT19937 does not match the reference");
+
+    let mut victim = Mt19937::new(rand::random());
+    let skip = args.skip.unwrap_or_else(|| rand::thread_rng().gen_range(0..10_000));
+    (0..skip).for_each(|_| {
+        victim.next_u32();
+    });
+    let observed: Vec<u32> = (0..Mt19937::N).map(|_| victim.next_u32()).collect();
+    println!("Victim: MT19937 with a random seed, {} outputs already drawn", skip);
+    println!("Observed {} consecutive outputs: {:#010x} {:#010x} ... {:#010x}", observed.len(), observed[0], observed[1], observed[Mt19937::N - 1]);
+
+    let start = Instant::now();
+    let mut clone = clone_from_outputs(&observed);
+    println!("Untempered into a full state in {:.2?}", start.elapsed());
+    let retempered = clone.mt.iter().zip(observed.iter()).all(|(&x, &y)| Mt19937::temper(x) == y);
+    println!("temper(untemper(y)) == y for all observed outputs: {}", retempered);
+
+    let mut all = true;
+    for i in 1..=args.predict {
+        let (p, v) = (clone.next_u32(), victim.next_u32());
+        all &= p == v;
+        println!("  next {:2}: predicted {:#010x}, victim {:#010x}{}", i, p, v, if p == v { "" } else { "  MISMATCH" });
+    }
+    println!("{}", if all { "All predictions match." } else { "Predictions differ from the victim." });
+}
+
+pub fn run_time_seed(args: &TimeSeedArgs) {
+    let observed = args.output.unwrap_or_else(|| {
+        let mut victim = Mt19937::seeded_from_time();
+        let skip = rand::thread_rng().gen_range(0..=args.max_skip);
+        (0..skip).for_each(|_| {
+            victim.next_u32();
+        });
+        let y = victim.next_u32();
+        println!("Victim output (Mt19937::seeded_from_time, after {} earlier outputs): {}", skip, y);
+        y
+    });
+    let now = now_secs();
+    let from = now.saturating_sub(args.window);
+    println!("Searching {} seconds ({} .. {}), output at up to {} outputs in", now - from + 1, from, now, args.max_skip);
+
+    let start = Instant::now();
+    // Newest first; each candidate costs an initialisation and one twist
+    let found = (from..=now).rev().find_map(|secs| {
+        let mut g = Mt19937::from_time(secs);
+        (0..=args.max_skip).find(|_| g.next_u32() == observed).map(|skip| (secs, skip))
+    });
+    let Some((secs, skip)) = found else {
+        println!("No seed in the window produces this output ({:.2?}).", start.elapsed());
+        std::process::exit(1);
+    };
+    println!("Found in {:.2?}", start.elapsed());
+    println!("Seed: Unix time {} ({} s before now), MT seed {:#010x}", secs, now - secs, (secs as u32) ^ 0x9E3779B9);
+    println!("Observed output is output {}", skip);
+
+    let mut replay = Mt19937::from_time(secs);
+    let before: Vec<u32> = (0..skip).map(|_| replay.next_u32()).collect();
+    if !before.is_empty() {
+        println!("Outputs before it: {:?}", before);
+    }
+    replay.next_u32();
+    let next: Vec<u32> = (0..5).map(|_| replay.next_u32()).collect();
+    println!("Next outputs:      {:?}", next);
+    // A 32-bit output matches a wrong seed with probability about window * skips / 2^32
+    println!("(One output can match a wrong seed by chance; check the next outputs against the victim.)");
+}
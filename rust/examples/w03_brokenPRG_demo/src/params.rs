@@ -0,0 +1,196 @@
+// Recovering an LCG's modulus, multiplier and increment from its outputs.
+//
+// The other attacks know `a`, `c` and `m`. Without them, a few consecutive full outputs
+// x_0, x_1, ... still give everything away. With differences t_i = x_{i+1} - x_i,
+//
+//   t_{i+1} = a t_i (mod m),  so  t_{i+2} t_i - t_{i+1}^2 = 0 (mod m)
+//
+// for each i: every 2x2 determinant of consecutive differences is a multiple of m. The
+// GCD of a few of them is m, or a small multiple of it that the outputs rule out (the
+// multiple is larger when the differences share a factor with m). Then
+//
+//   a = t_1 / t_0 (mod m),   c = x_1 - a x_0 (mod m)
+//
+// When t_0 has no inverse mod m (common for m = 2^32) the congruence has
+// gcd(t_0, m) solutions for a, and each is tried against the outputs.
+//
+// All arithmetic is on big integers, so moduli far beyond 64 bits work the same way.
+
+use std::time::Instant;
+
+use clap::{Args, ValueEnum};
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use rand::RngCore;
+
+use crate::{Lcg, Lcg32};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Victim {
+    /// `Lcg32` itself (m = 2^32, Numerical Recipes a and c)
+    Lcg32,
+    /// `Lcg` with a random modulus of --bits bits and random a, c
+    Random,
+}
+
+#[derive(Args, Debug)]
+pub struct ParamsArgs {
+    /// Generator that makes the observed outputs
+    #[arg(long, value_enum, default_value_t = Victim::Random)]
+    victim: Victim,
+
+    /// Modulus size for the random victim
+    #[arg(long, value_name = "BITS", default_value_t = 128, value_parser = clap::value_parser!(u64).range(8..=4096))]
+    bits: u64,
+
+    /// Consecutive outputs the attacker observes
+    #[arg(long, value_name = "N", default_value_t = 10, value_parser = clap::value_parser!(u8).range(5..=100))]
+    outputs: u8,
+
+    /// Attack these outputs (decimal, comma-separated) instead of a victim
+    #[arg(long, value_name = "X0,X1,...", value_delimiter = ',', conflicts_with_all = ["victim", "bits", "outputs"])]
+    values: Vec<String>,
+}
+
+/// Largest cofactor stripped from the GCD when it is a multiple of m
+const MAX_COFACTOR: u32 = 1 << 16;
+/// Largest gcd(t_0, m) for which every solution of a t_0 = t_1 is tried
+const MAX_SOLUTIONS: u64 = 1 << 16;
+/// Outputs compared against the victim after recovery
+const PREDICT: usize = 5;
+
+/// The victim's output stream
+type Outputs = Box<dyn FnMut() -> BigInt>;
+
+/// Recovered parameters
+pub struct Params {
+    pub m: BigInt,
+    pub a: BigInt,
+    pub c: BigInt,
+}
+
+pub fn run(args: &ParamsArgs) {
+    let (observed, mut victim): (Vec<BigInt>, Option<Outputs>) = if !args.values.is_empty() {
+        let parsed: Result<Vec<BigInt>, _> = args.values.iter().map(|v| v.trim().parse::<BigInt>()).collect();
+        match parsed {
+            Ok(v) if v.len() >= 5 && v.iter().all(|x| !x.is_negative()) => (v, None),
+            _ => {
+                eprintln!("--values needs at least 5 non-negative decimal integers");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        let mut next: Outputs = match args.victim {
+            Victim::Lcg32 => {
+                let mut g = Lcg32::seeded_from_time();
+                Box::new(move || BigInt::from(g.next_u32()))
+            }
+            Victim::Random => {
+                let mut g = random_lcg(args.bits);
+                println!("Victim: m = {}\n        a = {}\n        c = {}", g.m, g.a, g.c);
+                Box::new(move || g.next())
+            }
+        };
+        let observed = (0..args.outputs).map(|_| next()).collect();
+        (observed, Some(next))
+    };
+    println!("Observed {} outputs:", observed.len());
+    for x in observed.iter() {
+        println!("  {}", x);
+    }
+
+    let start = Instant::now();
+    let Some(p) = recover(&observed) else {
+        println!("No LCG fits these outputs ({:.2?}); observe more, or they are not consecutive LCG outputs.", start.elapsed());
+        std::process::exit(1);
+    };
+    println!("Recovered in {:.2?}:", start.elapsed());
+    let zeros = p.m.trailing_zeros().unwrap_or(0);
+    if p.m == BigInt::one() << zeros {
+        println!("  m = {} = 2^{}", p.m, zeros);
+    } else {
+        println!("  m = {} ({} bits)", p.m, p.m.bits());
+    }
+    println!("  a = {}", p.a);
+    println!("  c = {}", p.c);
+
+    let mut clone = Lcg { m: p.m, a: p.a, c: p.c, state: observed.last().expect("outputs").clone() };
+    let Some(victim) = victim.as_mut() else {
+        println!("Next {} outputs:", PREDICT);
+        for _ in 0..PREDICT {
+            println!("  {}", clone.next());
+        }
+        return;
+    };
+    let mut all = true;
+    for i in 1..=PREDICT {
+        let (p, v) = (clone.next(), victim());
+        all &= p == v;
+        println!("  next {}: predicted {}, victim {}{}", i, p, v, if p == v { "" } else { "  MISMATCH" });
+    }
+    println!("{}", if all { "All predictions match." } else { "Predictions differ from the victim." });
+}
+
+/// Random modulus with the top bit set, random a and c below it, random seed
+fn random_lcg(bits: u64) -> Lcg {
+    let mut rng = rand::thread_rng();
+    let mut random_bits = |bits: u64| {
+        let mut bytes = vec![0u8; bits.div_ceil(8) as usize];
+        rng.fill_bytes(&mut bytes);
+        BigInt::from_bytes_be(Sign::Plus, &bytes) % (BigInt::one() << bits)
+    };
+    let m = random_bits(bits - 1) | (BigInt::one() << (bits - 1));
+    let a = random_bits(bits) % &m;
+    let c = random_bits(bits) % &m;
+    let state = random_bits(bits) % &m;
+    Lcg { m, a, c, state }
+}
+
+/// m, a and c from consecutive full outputs (at least 5), checked against all of them
+pub fn recover(x: &[BigInt]) -> Option<Params> {
+    let t: Vec<BigInt> = x.windows(2).map(|w| &w[1] - &w[0]).collect();
+    let g = t.windows(3).map(|w| (&w[2] * &w[0] - &w[1] * &w[1]).abs()).fold(BigInt::zero(), |g, d| g.gcd(&d));
+    if g.is_zero() {
+        return None;
+    }
+    let largest = x.iter().max().expect("outputs");
+    // g is a multiple of m; try it and its quotients by small cofactors. The modulus must
+    // exceed every output. Multiples of m sometimes fit the outputs too, so the smallest
+    // modulus that fits wins.
+    (1..=MAX_COFACTOR)
+        .rev()
+        .map(BigInt::from)
+        .filter(|k| g.is_multiple_of(k))
+        .map(|k| &g / k)
+        .filter(|m| m > largest)
+        .find_map(|m| multiplier_and_increment(x, &t, &m))
+}
+
+/// a and c for modulus m, or None if no a, c reproduce all of `x`
+fn multiplier_and_increment(x: &[BigInt], t: &[BigInt], m: &BigInt) -> Option<Params> {
+    // Solve a t_0 = t_1 (mod m): with g = gcd(t_0, m) there are g solutions when g | t_1
+    let (t0, t1) = (t[0].mod_floor(m), t[1].mod_floor(m));
+    let g = t0.gcd(m);
+    if !t1.is_multiple_of(&g) {
+        return None;
+    }
+    let solutions = g.to_u64().filter(|&s| s <= MAX_SOLUTIONS)?;
+    let step = m / &g;
+    let base = (&t1 / &g * mod_inverse(&(&t0 / &g), &step)?).mod_floor(&step);
+    (0..solutions).find_map(|k| {
+        let a = &base + &step * k;
+        let c = (&x[1] - &a * &x[0]).mod_floor(m);
+        let mut g = Lcg { m: m.clone(), a: a.clone(), c: c.clone(), state: x[0].clone() };
+        x[1..].iter().all(|xi| &g.next() == xi).then(|| Params { m: m.clone(), a, c })
+    })
+}
+
+/// Inverse of `a` mod `m`, if gcd(a, m) = 1
+pub fn mod_inverse(a: &BigInt, m: &BigInt) -> Option<BigInt> {
+    if m.is_one() {
+        return Some(BigInt::zero());
+    }
+    let e = a.mod_floor(m).extended_gcd(m);
+    e.gcd.is_one().then(|| e.x.mod_floor(m))
+}
@@ -0,0 +1,101 @@
+// Time-seed brute force against `Lcg32::seeded_from_time`.
+//
+// The seed is the Unix time in seconds XORed with a public constant. If the attacker
+// knows roughly when a token was made - say within the last day - there are only
+// 86,400 candidate seeds. For each one, replay the generator and look for the token in
+// its output. A match gives the seed, so every output *before* the token (earlier
+// tokens, session IDs) and every output after it.
+//
+// The token need not be the first output: the search looks for it at each of the first
+// `max_skip` 4-byte positions.
+
+use clap::Args;
+
+use crate::{now_secs, Lcg32};
+
+#[derive(Args, Debug)]
+pub struct TimeSeedArgs {
+    /// Token bytes in hex (default: make a 16-byte token now, as a victim would)
+    #[arg(long, value_name = "HEX")]
+    token: Option<String>,
+
+    /// Search this many seconds back from now
+    #[arg(long, value_name = "SECS", default_value_t = 86_400)]
+    window: u64,
+
+    /// Start of the search window (Unix seconds); overrides --window
+    #[arg(long, value_name = "SECS", requires = "to")]
+    from: Option<u64>,
+
+    /// End of the search window (Unix seconds)
+    #[arg(long, value_name = "SECS", requires = "from")]
+    to: Option<u64>,
+
+    /// Outputs the generator may have produced before the token
+    #[arg(long, value_name = "N", default_value_t = 16)]
+    max_skip: usize,
+}
+
+/// Seed time and position of the token in the output.
+struct Found {
+    secs: u64,
+    skip: usize,
+}
+
+pub fn run(args: &TimeSeedArgs) {
+    let token = match &args.token {
+        Some(h) => match hex::decode(h.trim()) {
+            Ok(t) if !t.is_empty() => t,
+            _ => {
+                eprintln!("--token must be non-empty hex");
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let t = Lcg32::seeded_from_time().generate(16);
+            println!("Victim token (from Lcg32::seeded_from_time): {}", hex::encode(&t));
+            t
+        }
+    };
+    let now = now_secs();
+    let (from, to) = match (args.from, args.to) {
+        (Some(f), Some(t)) => (f.min(t), f.max(t)),
+        _ => (now.saturating_sub(args.window), now),
+    };
+    println!("Searching {} seconds ({} .. {}), token at up to {} outputs in", to - from + 1, from, to, args.max_skip);
+
+    let start = std::time::Instant::now();
+    let Some(found) = search(&token, from, to, args.max_skip) else {
+        println!("No seed in the window produces this token ({:.2?}).", start.elapsed());
+        std::process::exit(1);
+    };
+    println!("Found in {:.2?}", start.elapsed());
+    println!(
+        "Seed: Unix time {} ({} s before now), state {:#010x}",
+        found.secs,
+        now.saturating_sub(found.secs),
+        Lcg32::from_time(found.secs).state
+    );
+    println!("Token starts at output {}", found.skip);
+
+    let mut replay = Lcg32::from_time(found.secs);
+    let before = replay.generate(4 * found.skip);
+    if !before.is_empty() {
+        println!("Outputs before the token: {}", hex::encode(&before));
+    }
+    let again = replay.generate(token.len());
+    println!("Token reproduced:         {}", hex::encode(&again));
+    // generate() rounds up to whole outputs, so the next output follows the token's last word
+    println!("Next 16 bytes:            {}", hex::encode(replay.generate(16)));
+}
+
+/// Every second in [from, to], newest first (recent tokens are the likely ones)
+fn search(token: &[u8], from: u64, to: u64, max_skip: usize) -> Option<Found> {
+    let words = max_skip + token.len().div_ceil(4);
+    (from..=to).rev().find_map(|secs| {
+        let stream = Lcg32::from_time(secs).generate(4 * words);
+        (0..=max_skip)
+            .find(|&skip| stream[4 * skip..].starts_with(token))
+            .map(|skip| Found { secs, skip })
+    })
+}
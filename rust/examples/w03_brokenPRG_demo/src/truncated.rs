@@ -0,0 +1,204 @@
+// State recovery for LCGs that output only the high bits of their state.
+//
+// Hiding the low bits does not hide the state. Each output fixes `bits` bits of the
+// state, and the rest follows from the recurrence:
+//
+// - 32-bit state (`nr-high16`, `msvc`): the first output fixes the bits in the output
+//   window, and bits above it never influence any output. That leaves the 2^16 choices
+//   for the bits below the window instead of 2^32. Try each, step it, and keep the ones
+//   that reproduce the following outputs. One survives after two or three outputs.
+//
+// - 64-bit state (`mmix`, top 32 bits out): 2^32 states fit each output, too many to
+//   sweep. Write s_i = a^i s_0 + c_i (mod m) and s_i = 2^32 y_i + z_i with z_i < 2^32
+//   unknown. The vector t = (s_i - c_i)_i lies in the lattice of (a^i x mod m)_i and
+//   differs from the known u = (2^32 y_i - c_i)_i by the short vector z. LLL on the
+//   lattice with u appended (Kannan's embedding) brings (z - 2^31, -2^31) to the top of
+//   the basis; t_0 = s_0 follows. A handful of outputs is enough.
+
+use std::time::Instant;
+
+use clap::{Args, ValueEnum};
+use num_bigint::BigInt;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::lattice::lll;
+use crate::{TruncLcg32, TruncLcg64};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Variant {
+    /// Numerical Recipes constants, mod 2^32, top 16 bits out
+    NrHigh16,
+    /// Microsoft C `rand()`, mod 2^32, bits 16..31 out
+    Msvc,
+    /// Knuth's MMIX constants, mod 2^64, top 32 bits out
+    Mmix,
+}
+
+#[derive(Args, Debug)]
+pub struct TruncatedArgs {
+    /// Generator to attack
+    #[arg(long, value_enum, default_value_t = Variant::NrHigh16)]
+    variant: Variant,
+
+    /// Outputs the attacker observes (default: 4 for 32-bit state, 6 for mmix)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(2..=40))]
+    outputs: Option<u8>,
+
+    /// Victim's initial state in hex (default: random)
+    #[arg(long, value_name = "HEX")]
+    seed: Option<String>,
+}
+
+/// Outputs compared against the victim after recovery
+const PREDICT: usize = 5;
+
+pub fn run(args: &TruncatedArgs) {
+    let seed = match &args.seed {
+        Some(h) => match u64::from_str_radix(h.trim().trim_start_matches("0x"), 16) {
+            Ok(s) => s,
+            Err(_) => {
+                eprintln!("--seed must be hex");
+                std::process::exit(1);
+            }
+        },
+        None => rand::random(),
+    };
+    match args.variant {
+        Variant::NrHigh16 | Variant::Msvc => {
+            let make = if args.variant == Variant::Msvc { TruncLcg32::msvc } else { TruncLcg32::nr_high16 };
+            let mut victim = make(seed as u32);
+            let n = args.outputs.map_or(4, usize::from);
+            let observed: Vec<u32> = (0..n).map(|_| victim.next()).collect();
+            println!("Observed {} outputs of {} bits: {:x?}", n, victim.bits, observed);
+
+            let start = Instant::now();
+            let (states, tried) = brute_force(&victim, &observed);
+            println!("Tried {} of 2^32 states in {:.2?}", tried, start.elapsed());
+            let &[state] = states.as_slice() else {
+                println!("{} states fit these outputs; observe more (--outputs).", states.len());
+                std::process::exit(1);
+            };
+            let mut clone = make(state);
+            (1..n).for_each(|_| {
+                clone.next();
+            });
+            let window = victim.shift + victim.bits;
+            if window < 32 {
+                println!("State bits {} and up never reach an output; the state below is mod 2^{}.", window, window);
+            }
+            println!("Recovered state after the first output: {:#010x}", state);
+            println!("Victim's initial state (stepped back):  {:#010x}", step_back_32(&clone, state) & mask(window));
+            report_predictions(|| clone.next() as u64, || victim.next() as u64);
+        }
+        Variant::Mmix => {
+            let mut victim = TruncLcg64 { state: seed };
+            let n = args.outputs.map_or(6, usize::from);
+            let observed: Vec<u32> = (0..n).map(|_| victim.next()).collect();
+            println!("Observed {} outputs of 32 bits: {:x?}", n, observed);
+
+            let start = Instant::now();
+            let Some(state) = lattice_attack(&observed) else {
+                println!("LLL did not expose the state ({:.2?}); observe more (--outputs).", start.elapsed());
+                std::process::exit(1);
+            };
+            println!("LLL on a {}-dimensional lattice in {:.2?}", n + 1, start.elapsed());
+            let mut clone = TruncLcg64 { state };
+            (1..n).for_each(|_| {
+                clone.next();
+            });
+            let seed_back = (state.wrapping_sub(TruncLcg64::C)).wrapping_mul(inverse_odd(TruncLcg64::A));
+            println!("Recovered state after the first output: {:#018x}", state);
+            println!("Victim's initial state (stepped back):  {:#018x}", seed_back);
+            report_predictions(|| clone.next() as u64, || victim.next() as u64);
+        }
+    }
+}
+
+fn report_predictions(mut predicted: impl FnMut() -> u64, mut actual: impl FnMut() -> u64) {
+    let mut all = true;
+    for i in 1..=PREDICT {
+        let (p, a) = (predicted(), actual());
+        all &= p == a;
+        println!("  next {}: predicted {:#010x}, victim {:#010x}{}", i, p, a, if p == a { "" } else { "  MISMATCH" });
+    }
+    println!("{}", if all { "All predictions match." } else { "Predictions differ from the victim." });
+}
+
+/// Every state (after the first step) that reproduces `observed`, and how many were tried.
+///
+/// Carries only move up, so state bits above the output window never reach an output:
+/// the states are only determined (and only need to be) mod 2^(shift + bits). That leaves
+/// the `shift` bits below the window to try.
+fn brute_force(params: &TruncLcg32, observed: &[u32]) -> (Vec<u32>, u64) {
+    let known = observed[0] << params.shift;
+    let tried: u32 = 1 << params.shift;
+    let found = (0..tried)
+        .map(|low| known | low)
+        .filter(|&state| {
+            let mut g = TruncLcg32 { state, ..*params };
+            observed[1..].iter().all(|&y| g.next() == y)
+        })
+        .collect();
+    (found, tried as u64)
+}
+
+fn step_back_32(params: &TruncLcg32, state: u32) -> u32 {
+    state.wrapping_sub(params.c).wrapping_mul(inverse_odd(params.a as u64) as u32)
+}
+
+fn mask(bits: u32) -> u32 {
+    if bits >= 32 { u32::MAX } else { (1 << bits) - 1 }
+}
+
+/// Inverse of an odd number mod 2^64 (Newton's iteration doubles the correct bits)
+fn inverse_odd(a: u64) -> u64 {
+    let mut x = a; // correct to 3 bits: a * a = 1 mod 8 for odd a
+    for _ in 0..5 {
+        x = x.wrapping_mul(2u64.wrapping_sub(a.wrapping_mul(x)));
+    }
+    x
+}
+
+/// State after the first step of a `TruncLcg64` from its top-32-bit outputs
+fn lattice_attack(observed: &[u32]) -> Option<u64> {
+    let n = observed.len();
+    let modulus: BigInt = BigInt::one() << 64;
+    let half: BigInt = BigInt::one() << 31;
+
+    // s_i = a^i s_0 + c_i, with c_0 = 0 and c_i = a c_{i-1} + c (all mod 2^64)
+    let (mut power, mut offset) = (1u64, 0u64);
+    let mut powers = Vec::with_capacity(n);
+    let mut targets = Vec::with_capacity(n);
+    for &y in observed {
+        powers.push(BigInt::from(power));
+        // centre z_i in [-2^31, 2^31) so the short vector is as short as possible
+        targets.push(BigInt::from(((y as u64) << 32).wrapping_sub(offset).wrapping_add(1 << 31)));
+        power = power.wrapping_mul(TruncLcg64::A);
+        offset = offset.wrapping_mul(TruncLcg64::A).wrapping_add(TruncLcg64::C);
+    }
+
+    let mut basis = vec![vec![BigInt::zero(); n + 1]; n + 1];
+    basis[0][..n].clone_from_slice(&powers);
+    for (i, row) in basis.iter_mut().enumerate().take(n).skip(1) {
+        row[i] = modulus.clone();
+    }
+    basis[n][..n].clone_from_slice(&targets);
+    basis[n][n] = half.clone();
+    lll(&mut basis);
+
+    basis.iter().find_map(|row| {
+        let sign = if row[n] == -&half {
+            1
+        } else if row[n] == half {
+            -1
+        } else {
+            return None;
+        };
+        let t0: BigInt = (&targets[0] + sign * &row[0]) % &modulus;
+        let t0 = if t0 < BigInt::zero() { t0 + &modulus } else { t0 };
+        let state = t0.to_u64()?;
+        let mut g = TruncLcg64 { state };
+        // the state already produced observed[0]; check it and the rest
+        ((state >> 32) as u32 == observed[0] && observed[1..].iter().all(|&y| g.next() == y)).then_some(state)
+    })
+}
@@ -0,0 +1,69 @@
+// detect.rs
+// Pad reuse detector. If two ciphertexts used the same pad bytes, XORing them cancels
+// the pad: c1 ^ c2 = p1 ^ p2. For text plaintexts (ASCII, top bit clear) every byte of
+// p1 ^ p2 has its top bit clear too, while bytes of unrelated pad are random and clear
+// it only half the time. Over n aligned bytes the count of clear top bits is therefore
+// ~n/2 +- sqrt(n)/2 without reuse and ~n with it; its z-score separates the two.
+//
+// Where the segments sit relative to each other is unknown for bare ciphertexts, so
+// every shift up to a limit is tried. Headers naming the same pad (equal pad ids) give the
+// shift directly, and an overlap of the recorded ranges is reuse in itself. Headers naming
+// different pads say nothing about the shift, so those pairs are scanned like bare ones.
+
+use crate::pad::Message;
+
+/// Aligned bytes needed before a shift is judged
+const MIN_OVERLAP: usize = 16;
+/// z-score above which a pair is flagged
+pub const Z_FLAG: f64 = 5.0;
+
+/// Strongest evidence of shared pad between two ciphertexts.
+pub struct Finding {
+    /// Pad offset of the second message minus that of the first
+    pub shift: i64,
+    pub overlap: usize,
+    pub z: f64,
+    /// Overlap of the ranges recorded in the headers, if both have one from the same pad
+    pub header_overlap: Option<u64>,
+    /// p1 ^ p2 over the overlap at the best shift
+    pub xor: Vec<u8>,
+}
+
+/// Compare two messages (`headers`: whether each carried a header).
+pub fn compare(a: &Message, b: &Message, headers: (bool, bool), max_shift: usize) -> Option<Finding> {
+    let header_overlap = if headers.0 && headers.1 && a.pad_id == b.pad_id {
+        // Offsets come from the file, so a forged header must not overflow
+        let start = a.offset.max(b.offset);
+        let end = a.offset.saturating_add(a.ciphertext.len() as u64).min(b.offset.saturating_add(b.ciphertext.len() as u64));
+        Some(end.saturating_sub(start))
+    } else {
+        None
+    };
+
+    // With headers the shift is known; otherwise scan
+    let shifts: Vec<i64> = match header_overlap {
+        Some(_) => vec![b.offset.wrapping_sub(a.offset) as i64],
+        None => (-(max_shift as i64)..=max_shift as i64).collect(),
+    };
+    let mut best: Option<Finding> = None;
+    for shift in shifts {
+        // a[k] lines up with b[k - shift]
+        let (a_from, b_from) = if shift >= 0 { (shift as usize, 0) } else { (0, shift.unsigned_abs() as usize) };
+        if a_from >= a.ciphertext.len() || b_from >= b.ciphertext.len() {
+            continue;
+        }
+        let n = (a.ciphertext.len() - a_from).min(b.ciphertext.len() - b_from);
+        if n < MIN_OVERLAP {
+            continue;
+        }
+        let x: Vec<u8> = a.ciphertext[a_from..a_from + n].iter().zip(&b.ciphertext[b_from..b_from + n]).map(|(p, q)| p ^ q).collect();
+        let clear = x.iter().filter(|&&v| v < 0x80).count();
+        let z = (2.0 * clear as f64 - n as f64) / (n as f64).sqrt();
+        if best.as_ref().is_none_or(|f| z > f.z) {
+            best = Some(Finding { shift, overlap: n, z, header_overlap, xor: x });
+        }
+    }
+    best.or_else(|| {
+        header_overlap.map(|h| Finding { shift: b.offset.wrapping_sub(a.offset) as i64, overlap: 0, z: 0.0, header_overlap: Some(h), xor: Vec::new() })
+    })
+}
@@ -0,0 +1,164 @@
+// one_time_pad.rs
+// One-time pad: c = p XOR k with k truly random, as long as the message, used once.
+// Usage: cargo run -- --help
+//
+//   cargo run -- gen pad.bin --size 1000000      (both parties keep a copy)
+//   cargo run -- encrypt --pad pad.bin --input msg.txt --output msg.otp
+//   cargo run -- decrypt --pad pad.bin --input msg.otp
+//   cargo run -- status pad.bin
+//   cargo run -- detect a.otp b.otp ...          (flag ciphertexts that share pad)
+//
+// Compare the week's other keystreams: `xor_block` (w01) repeats one key byte, so the
+// ciphertext leaks p1 ^ p2 between any two blocks; `prg_chacha20` (w03_workingPRG)
+// stretches 32 bytes of seed, secure only against attackers who cannot break ChaCha20.
+// Here every key byte is independent and uniform, so the ciphertext says nothing about
+// the plaintext beyond its length (perfect secrecy) - as long as no pad byte is ever used
+// twice. Reuse brings back c1 ^ c2 = p1 ^ p2, which is why the pad file carries an offset
+// pointer that only moves forward and `detect` looks for reuse.
+//
+// Use one pad per direction: if both parties encrypt from the same pad file, each one's
+// pointer knows nothing about the other's messages.
+
+mod detect;
+mod pad;
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(name = "w03_oneTimePad_demo", about = "One-time pad with pad files and reuse detection")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Create a new pad file of random bytes from the OS (never overwrites)
+    Gen {
+        pad: PathBuf,
+        /// Pad size in bytes
+        #[arg(long, value_name = "BYTES")]
+        size: u64,
+    },
+    /// Encrypt with the next unused pad bytes and advance the pointer past them
+    Encrypt {
+        #[arg(long, value_name = "FILE")]
+        pad: PathBuf,
+        /// Plaintext file (default: stdin)
+        #[arg(short, long, value_name = "FILE")]
+        input: Option<PathBuf>,
+        /// Message file to write
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+    },
+    /// Decrypt a message file with the pad segment named in its header
+    Decrypt {
+        #[arg(long, value_name = "FILE")]
+        pad: PathBuf,
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+        /// Plaintext file to write (default: stdout)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Show how much of a pad is used
+    Status { pad: PathBuf },
+    /// Flag pairs of ciphertexts (message files or bare bytes) that likely share pad
+    Detect {
+        #[arg(required = true, num_args = 2..)]
+        files: Vec<PathBuf>,
+        /// Largest relative shift tried for ciphertexts without a header
+        #[arg(long, value_name = "N", default_value_t = 1024)]
+        max_shift: usize,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Gen { pad, size } => {
+            or_exit(pad::generate(&pad, size), &pad);
+            println!("Wrote {} bytes of pad to {}", size, pad.display());
+        }
+        Command::Encrypt { pad, input, output } => {
+            let plaintext = match &input {
+                Some(path) => or_exit(fs::read(path), path),
+                None => {
+                    let mut buf = Vec::new();
+                    or_exit(io::stdin().read_to_end(&mut buf), "stdin");
+                    buf
+                }
+            };
+            let msg = or_exit(pad::encrypt(&pad, &plaintext), &pad);
+            or_exit(fs::write(&output, msg.to_bytes()), &output);
+            let st = or_exit(pad::status(&pad), &pad);
+            println!(
+                "Encrypted {} bytes with pad bytes {}..{}; {} bytes of pad left",
+                plaintext.len(),
+                msg.offset,
+                msg.offset + plaintext.len() as u64,
+                st.size - st.used
+            );
+        }
+        Command::Decrypt { pad, input, output } => {
+            let (msg, header) = pad::Message::from_bytes(&or_exit(fs::read(&input), &input));
+            if !header {
+                eprintln!("{}: no message header, so the pad offset is unknown", input.display());
+                std::process::exit(1);
+            }
+            let plaintext = or_exit(pad::decrypt(&pad, &msg), &pad);
+            match output {
+                Some(path) => or_exit(fs::write(&path, plaintext), &path),
+                None => or_exit(io::stdout().write_all(&plaintext), "stdout"),
+            }
+        }
+        Command::Status { pad } => {
+            let st = or_exit(pad::status(&pad), &pad);
+            println!("Pad {} (id {}): {} bytes, {} used, {} left", pad.display(), hex::encode(st.id), st.size, st.used, st.size - st.used);
+        }
+        Command::Detect { files, max_shift } => detect(&files, max_shift),
+    }
+}
+
+fn detect(files: &[PathBuf], max_shift: usize) {
+    let msgs: Vec<(pad::Message, bool)> = files.iter().map(|f| pad::Message::from_bytes(&or_exit(fs::read(f), f))).collect();
+    let mut flagged = 0;
+    println!("{:<20} {:<20} {:>7} {:>8} {:>8}  verdict", "first", "second", "shift", "overlap", "z");
+    for i in 0..msgs.len() {
+        for j in (i + 1)..msgs.len() {
+            let (a, b) = (&msgs[i], &msgs[j]);
+            let name = |k: usize| files[k].display().to_string();
+            let Some(f) = detect::compare(&a.0, &b.0, (a.1, b.1), max_shift) else {
+                println!("{:<20} {:<20} too short to compare", name(i), name(j));
+                continue;
+            };
+            let reused = f.z >= detect::Z_FLAG || f.header_overlap.is_some_and(|h| h > 0);
+            let verdict = match (reused, f.header_overlap) {
+                (true, Some(h)) if h > 0 => format!("REUSED PAD (headers overlap by {} bytes)", h),
+                (true, _) => "REUSED PAD".to_string(),
+                (false, _) => "independent".to_string(),
+            };
+            println!("{:<20} {:<20} {:>+7} {:>8} {:>8.1}  {}", name(i), name(j), f.shift, f.overlap, f.z, verdict);
+            if reused {
+                flagged += 1;
+            }
+            if reused && !f.xor.is_empty() {
+                let shown = &f.xor[..f.xor.len().min(32)];
+                println!("    c1 ^ c2 = p1 ^ p2: {}{}", hex::encode(shown), if f.xor.len() > 32 { "..." } else { "" });
+            }
+        }
+    }
+    println!("\n{} pair(s) flagged (z >= {}: far more clear top bits than random pad gives)", flagged, detect::Z_FLAG);
+}
+
+/// Unwrap or print the error against `what` and exit
+fn or_exit<T, E: std::fmt::Display>(r: Result<T, E>, what: impl AsRef<std::path::Path>) -> T {
+    r.unwrap_or_else(|e| {
+        eprintln!("{}: {}", what.as_ref().display(), e);
+        std::process::exit(1);
+    })
+}
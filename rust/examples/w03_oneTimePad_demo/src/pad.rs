@@ -0,0 +1,159 @@
+// pad.rs
+// Pad files and one-time-pad messages.
+//
+// Pad file:  "OTPPAD2\0" | pad id (8 bytes) | next unused offset (u64 LE) | pad bytes
+// Message:   "OTPMSG2\0" | pad id (8 bytes) | pad offset used (u64 LE)    | ciphertext
+//
+// The pad id is drawn at random when the pad is made (it is not pad material), so two
+// messages name the same pad exactly when their ids agree and offsets can be compared.
+//
+// The offset pointer only moves forward. It is written and synced to disk *before* the
+// ciphertext is released, so a crash can waste pad but never hand out the same bytes
+// twice. The file is locked while the pointer is read and advanced.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+const PAD_MAGIC: &[u8; 8] = b"OTPPAD2\0";
+const MSG_MAGIC: &[u8; 8] = b"OTPMSG2\0";
+const HEADER: u64 = 24;
+/// Where the offset pointer sits in the pad header
+const POINTER: u64 = 16;
+
+/// Where a pad stands: total size and how much is already used.
+pub struct PadStatus {
+    pub id: [u8; 8],
+    pub size: u64,
+    pub used: u64,
+}
+
+/// An encrypted message and the pad segment it used.
+pub struct Message {
+    pub pad_id: [u8; 8],
+    pub offset: u64,
+    pub ciphertext: Vec<u8>,
+}
+
+/// Create a new pad of `size` random bytes from the operating system. Refuses to
+/// overwrite an existing file: a regenerated pad would strand messages made with the old one.
+pub fn generate(path: &Path, size: u64) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    let mut id = [0u8; 8];
+    OsRng.fill_bytes(&mut id);
+    file.write_all(PAD_MAGIC)?;
+    file.write_all(&id)?;
+    file.write_all(&0u64.to_le_bytes())?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut left = size;
+    while left > 0 {
+        let n = left.min(buf.len() as u64) as usize;
+        OsRng.fill_bytes(&mut buf[..n]);
+        file.write_all(&buf[..n])?;
+        left -= n as u64;
+    }
+    file.sync_all()
+}
+
+/// Open a pad file and check its header; returns the file and the offset pointer.
+/// Opened for writing, the file stays locked until it is dropped.
+fn open(path: &Path, write: bool) -> io::Result<(File, PadStatus)> {
+    let mut file = OpenOptions::new().read(true).write(write).open(path)?;
+    if write {
+        file.lock()?;
+    }
+    let mut header = [0u8; HEADER as usize];
+    file.read_exact(&mut header).map_err(|_| invalid("too short for a pad file"))?;
+    if &header[..8] != PAD_MAGIC {
+        return Err(invalid("not a pad file"));
+    }
+    let id: [u8; 8] = header[8..16].try_into().expect("8 bytes");
+    let used = u64::from_le_bytes(header[16..].try_into().expect("8 bytes"));
+    let size = file.metadata()?.len().saturating_sub(HEADER);
+    if used > size {
+        return Err(invalid("offset pointer beyond the end of the pad"));
+    }
+    Ok((file, PadStatus { id, size, used }))
+}
+
+pub fn status(path: &Path) -> io::Result<PadStatus> {
+    open(path, false).map(|(_, s)| s)
+}
+
+/// Take the next `len` unused pad bytes, advancing the pointer past them for good.
+fn consume(path: &Path, len: u64) -> io::Result<([u8; 8], u64, Vec<u8>)> {
+    let (mut file, st) = open(path, true)?;
+    if st.size - st.used < len {
+        return Err(io::Error::other(format!(
+            "pad exhausted: {} bytes left, message needs {}; refusing to wrap around and reuse pad",
+            st.size - st.used,
+            len
+        )));
+    }
+    let mut key = vec![0u8; len as usize];
+    file.seek(SeekFrom::Start(HEADER + st.used))?;
+    file.read_exact(&mut key)?;
+    file.seek(SeekFrom::Start(POINTER))?;
+    file.write_all(&(st.used + len).to_le_bytes())?;
+    file.sync_all()?;
+    Ok((st.id, st.used, key))
+}
+
+/// Read `len` pad bytes at `offset` without moving the pointer (the receiver's side)
+fn segment(path: &Path, pad_id: [u8; 8], offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    let (mut file, st) = open(path, false)?;
+    if pad_id != st.id {
+        return Err(invalid("message was encrypted with another pad (pad ids differ)"));
+    }
+    if offset.checked_add(len).is_none_or(|end| end > st.size) {
+        return Err(invalid("message runs past the end of this pad: wrong pad?"));
+    }
+    let mut key = vec![0u8; len as usize];
+    file.seek(SeekFrom::Start(HEADER + offset))?;
+    file.read_exact(&mut key)?;
+    Ok(key)
+}
+
+pub fn encrypt(path: &Path, plaintext: &[u8]) -> io::Result<Message> {
+    let (pad_id, offset, key) = consume(path, plaintext.len() as u64)?;
+    Ok(Message { pad_id, offset, ciphertext: xor(plaintext, &key) })
+}
+
+pub fn decrypt(path: &Path, msg: &Message) -> io::Result<Vec<u8>> {
+    let key = segment(path, msg.pad_id, msg.offset, msg.ciphertext.len() as u64)?;
+    Ok(xor(&msg.ciphertext, &key))
+}
+
+pub fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+impl Message {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER as usize + self.ciphertext.len());
+        out.extend_from_slice(MSG_MAGIC);
+        out.extend_from_slice(&self.pad_id);
+        out.extend_from_slice(&self.offset.to_le_bytes());
+        out.extend_from_slice(&self.ciphertext);
+        out
+    }
+
+    /// Parse a message file; anything without the header is taken as bare ciphertext
+    /// (offset unknown), which the reuse detector can still examine.
+    pub fn from_bytes(bytes: &[u8]) -> (Self, bool) {
+        if bytes.len() >= HEADER as usize && &bytes[..8] == MSG_MAGIC {
+            let pad_id = bytes[8..16].try_into().expect("8 bytes");
+            let offset = u64::from_le_bytes(bytes[16..24].try_into().expect("8 bytes"));
+            (Message { pad_id, offset, ciphertext: bytes[HEADER as usize..].to_vec() }, true)
+        } else {
+            (Message { pad_id: [0; 8], offset: 0, ciphertext: bytes.to_vec() }, false)
+        }
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
@@ -0,0 +1,203 @@
+// ChaCha from scratch, to see what `ChaCha20Rng` does inside.
+//
+// The state is 16 32-bit words (RFC 8439, section 2.3):
+//
+//   cccccccc  cccccccc  cccccccc  cccccccc      c = "expand 32-byte k"
+//   kkkkkkkk  kkkkkkkk  kkkkkkkk  kkkkkkkk      k = key, little-endian words
+//   kkkkkkkk  kkkkkkkk  kkkkkkkk  kkkkkkkk
+//   bbbbbbbb  nnnnnnnn  nnnnnnnn  nnnnnnnn      b = block counter, n = nonce
+//
+// Bernstein's original layout, which `ChaCha20Rng` uses, splits the last row into a
+// 64-bit counter and a 64-bit nonce (its "stream"). With a counter below 2^32 the two
+// agree when the RFC nonce is four zero bytes followed by the 64-bit one.
+//
+// A block is `rounds` rounds of quarter rounds (alternately on columns and diagonals),
+// then the input state is added back in, so the rounds cannot simply be run backwards.
+// The output is the 16 words serialised little-endian. ChaCha20 does 20 rounds; ChaCha12
+// and ChaCha8 trade margin for speed (the best attacks reach about 7 rounds).
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::{ChaCha12Rng, ChaCha20Rng, ChaCha8Rng};
+
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+/// a += b; d ^= a; d <<<= 16;  c += d; b ^= c; b <<<= 12;
+/// a += b; d ^= a; d <<<= 8;   c += d; b ^= c; b <<<= 7;
+pub fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+fn key_words(state: &mut [u32; 16], key: &[u8; 32]) {
+    state[..4].copy_from_slice(&CONSTANTS);
+    for (w, chunk) in state[4..12].iter_mut().zip(key.chunks_exact(4)) {
+        *w = u32::from_le_bytes(chunk.try_into().expect("4 bytes"));
+    }
+}
+
+/// RFC 8439 input: 32-bit counter, 96-bit nonce
+pub fn rfc8439_state(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u32; 16] {
+    let mut s = [0u32; 16];
+    key_words(&mut s, key);
+    s[12] = counter;
+    for (w, chunk) in s[13..].iter_mut().zip(nonce.chunks_exact(4)) {
+        *w = u32::from_le_bytes(chunk.try_into().expect("4 bytes"));
+    }
+    s
+}
+
+/// Original input: 64-bit counter, 64-bit nonce (the layout of `ChaCha20Rng`)
+pub fn original_state(key: &[u8; 32], counter: u64, nonce: u64) -> [u32; 16] {
+    let mut s = [0u32; 16];
+    key_words(&mut s, key);
+    s[12] = counter as u32;
+    s[13] = (counter >> 32) as u32;
+    s[14] = nonce as u32;
+    s[15] = (nonce >> 32) as u32;
+    s
+}
+
+/// One 64-byte keystream block after `rounds` rounds (an even number)
+pub fn block(input: &[u32; 16], rounds: usize) -> [u8; 64] {
+    assert!(rounds.is_multiple_of(2), "ChaCha rounds come in column/diagonal pairs");
+    let mut s = *input;
+    for _ in 0..rounds / 2 {
+        // columns
+        quarter_round(&mut s, 0, 4, 8, 12);
+        quarter_round(&mut s, 1, 5, 9, 13);
+        quarter_round(&mut s, 2, 6, 10, 14);
+        quarter_round(&mut s, 3, 7, 11, 15);
+        // diagonals
+        quarter_round(&mut s, 0, 5, 10, 15);
+        quarter_round(&mut s, 1, 6, 11, 12);
+        quarter_round(&mut s, 2, 7, 8, 13);
+        quarter_round(&mut s, 3, 4, 9, 14);
+    }
+    let mut out = [0u8; 64];
+    for (i, chunk) in out.chunks_exact_mut(4).enumerate() {
+        chunk.copy_from_slice(&s[i].wrapping_add(input[i]).to_le_bytes());
+    }
+    out
+}
+
+/// Keystream of `len` bytes in the original layout from block `counter` on
+pub fn keystream(key: &[u8; 32], nonce: u64, counter: u64, rounds: usize, len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len + 64);
+    let mut counter = counter;
+    while out.len() < len {
+        out.extend_from_slice(&block(&original_state(key, counter, nonce), rounds));
+        counter = counter.wrapping_add(1);
+    }
+    out.truncate(len);
+    out
+}
+
+/// RFC 8439 encryption (section 2.4): XOR with the keystream from block `counter` on
+pub fn rfc8439_encrypt(key: &[u8; 32], counter: u32, nonce: &[u8; 12], data: &[u8]) -> Vec<u8> {
+    data.chunks(64)
+        .zip(counter..)
+        .flat_map(|(chunk, ctr)| {
+            let ks = block(&rfc8439_state(key, ctr, nonce), 20);
+            chunk.iter().zip(ks).map(|(d, k)| d ^ k).collect::<Vec<u8>>()
+        })
+        .collect()
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    hex::decode(s.split_whitespace().collect::<String>()).expect("test vector hex")
+}
+
+/// Run all checks and the timing; returns whether every check passed
+pub fn run(mib: usize) -> bool {
+    let mut all = true;
+    let mut check = |name: &str, ok: bool| {
+        all &= ok;
+        println!("  {:<58} {}", name, if ok { "ok" } else { "FAILED" });
+    };
+    let counting: [u8; 32] = std::array::from_fn(|i| i as u8);
+
+    println!("RFC 8439 test vectors:");
+    let mut s = [0u32; 16];
+    (s[0], s[1], s[2], s[3]) = (0x11111111, 0x01020304, 0x9b8d6f43, 0x01234567);
+    quarter_round(&mut s, 0, 1, 2, 3);
+    check("2.1.1 quarter round", s[..4] == [0xea2a92f4, 0xcb1cf8ce, 0x4581472e, 0x5881c4bb]);
+
+    let nonce: [u8; 12] = unhex("000000090000004a00000000").try_into().expect("12 bytes");
+    let want = unhex(
+        "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e
+         d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e",
+    );
+    check("2.3.2 block function (counter 1)", block(&rfc8439_state(&counting, 1, &nonce), 20)[..] == want[..]);
+
+    let nonce: [u8; 12] = unhex("000000000000004a00000000").try_into().expect("12 bytes");
+    let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+    let want = unhex(
+        "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b
+         f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8
+         07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736
+         5af90bbf74a35be6b40b8eedf2785e42874d",
+    );
+    check("2.4.2 encryption (114 bytes)", rfc8439_encrypt(&counting, 1, &nonce, plaintext) == want);
+
+    let want = unhex(
+        "76b8e0ada0f13d90405d6ae55386bd28bdd219b8a08ded1aa836efcc8b770dc7
+         da41597c5157488d7724e03fb8d84a376a43b8f41518a11cc387b669b2ee6586",
+    );
+    check("A.1 #1: zero key, zero nonce, counter 0", block(&rfc8439_state(&[0; 32], 0, &[0; 12]), 20)[..] == want[..]);
+
+    println!("Against rand_chacha:");
+    let mut key = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut key);
+    let stream = rand::rngs::OsRng.next_u64();
+    check("prg_chacha20 (ChaCha20Rng::from_seed), 4096 bytes", keystream(&key, 0, 0, 20, 4096) == crate::prg_chacha20(key, 4096));
+
+    let mut rng = ChaCha20Rng::from_seed(key);
+    let mut theirs = vec![0u8; 4096];
+
+    rng.set_stream(stream);
+    // 16 words per block; start two blocks before the low counter word wraps
+    rng.set_word_pos(((1u128 << 32) - 2) * 16);
+    rng.fill_bytes(&mut theirs);
+    check("random nonce, counter 2^32 - 2 (carry into the high word)", keystream(&key, stream, (1 << 32) - 2, 20, 4096) == theirs);
+
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&stream.to_le_bytes());
+    rng.set_word_pos(5 * 16);
+    rng.fill_bytes(&mut theirs[..64]);
+    check("RFC layout, nonce = 0^4 || stream, counter 5", block(&rfc8439_state(&key, 5, &nonce), 20)[..] == theirs[..64]);
+
+    let mut r8 = ChaCha8Rng::from_seed(key);
+    r8.fill_bytes(&mut theirs);
+    check("ChaCha8 against ChaCha8Rng, 4096 bytes", keystream(&key, 0, 0, 8, 4096) == theirs);
+    let mut r12 = ChaCha12Rng::from_seed(key);
+    r12.fill_bytes(&mut theirs);
+    check("ChaCha12 against ChaCha12Rng, 4096 bytes", keystream(&key, 0, 0, 12, 4096) == theirs);
+
+    let len = mib << 20;
+    println!("Timing, {} MiB of keystream{}:", mib, if cfg!(debug_assertions) { " (debug build; use --release for real numbers)" } else { "" });
+    let time = |name: &str, f: &mut dyn FnMut()| {
+        let start = Instant::now();
+        f();
+        let secs = start.elapsed().as_secs_f64();
+        println!("  {:<36} {:>8.1} ms {:>9.1} MiB/s", name, 1000.0 * secs, mib as f64 / secs);
+    };
+    for rounds in [20, 12, 8] {
+        time(&format!("from scratch, {} rounds", rounds), &mut || {
+            black_box(keystream(&key, 0, 0, rounds, len));
+        });
+    }
+    let mut buf = vec![0u8; len];
+    time("ChaCha20Rng", &mut || ChaCha20Rng::from_seed(key).fill_bytes(black_box(&mut buf)));
+    time("ChaCha12Rng", &mut || ChaCha12Rng::from_seed(key).fill_bytes(black_box(&mut buf)));
+    time("ChaCha8Rng", &mut || ChaCha8Rng::from_seed(key).fill_bytes(black_box(&mut buf)));
+    all
+}
@@ -0,0 +1,675 @@
+// The NIST SP 800-90A deterministic random bit generators: HMAC_DRBG, Hash_DRBG and
+// CTR_DRBG.
+//
+// Each one is a PRG like `prg_chacha20`, wrapped in a life cycle:
+//
+//   instantiate(entropy, nonce, personalisation)   working state from fresh entropy
+//   reseed(entropy, additional input)              mix in new entropy, reset the counter
+//   generate(n, additional input)                  output, then update the state
+//
+// The update after every request gives backtracking resistance: a state captured later
+// does not reveal earlier output. The reseed counter limits how long one seed is
+// stretched (2^48 requests here); past that, generate refuses until reseeded. Prediction
+// resistance goes further and reseeds from the entropy source before every request, so
+// a captured state does not predict later output either.
+//
+// Additional input is optional caller data (a timestamp, a transaction id) that is
+// mixed into the state on each request. It is not secret and does not replace entropy.
+//
+// The mechanisms implement `Drbg`. `Instance` adds an entropy source and the reseed
+// policy, and implements `RngCore`, the trait `ChaCha20Rng` implements, so any of them
+// can stand in for the generator in `prg_chacha20`.
+
+use std::collections::VecDeque;
+
+use aes::cipher::{BlockEncrypt, KeyInit};
+use aes::{Aes128, Aes256};
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+
+/// Requests allowed between reseeds (SP 800-90A, table 2)
+pub const RESEED_INTERVAL: u64 = 1 << 48;
+/// Largest single request: 2^19 bits
+pub const MAX_REQUEST: usize = 1 << 16;
+
+/// The reseed counter has passed `RESEED_INTERVAL`
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReseedRequired;
+
+pub trait Drbg {
+    fn name(&self) -> String;
+    /// Entropy input length for a reseed, in bytes
+    fn entropy_len(&self) -> usize;
+    fn reseed(&mut self, entropy: &[u8], additional: &[u8]);
+    /// Fill `out` (at most `MAX_REQUEST` bytes)
+    fn generate(&mut self, out: &mut [u8], additional: &[u8]) -> Result<(), ReseedRequired>;
+    /// Requests since the last (re)seed, plus one
+    fn reseed_counter(&self) -> u64;
+}
+
+fn check_request(out: &[u8], reseed_counter: u64) -> Result<(), ReseedRequired> {
+    assert!(out.len() <= MAX_REQUEST, "requests are limited to {} bytes", MAX_REQUEST);
+    if reseed_counter > RESEED_INTERVAL {
+        return Err(ReseedRequired);
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------- HMAC_DRBG (10.1.2)
+
+/// HMAC_DRBG with HMAC-SHA-256: state (K, V)
+pub struct HmacDrbg {
+    k: [u8; 32],
+    v: [u8; 32],
+    reseed_counter: u64,
+}
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC takes any key length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+impl HmacDrbg {
+    pub fn new(entropy: &[u8], nonce: &[u8], personalisation: &[u8]) -> Self {
+        let mut d = Self { k: [0; 32], v: [1; 32], reseed_counter: 1 };
+        d.update(&[entropy, nonce, personalisation]);
+        d
+    }
+
+    /// K = HMAC(K, V || 0x00 || data), V = HMAC(K, V), and again with 0x01 if there is data
+    fn update(&mut self, data: &[&[u8]]) {
+        let empty = data.iter().all(|d| d.is_empty());
+        for round in [0u8, 1] {
+            if round == 1 && empty {
+                break;
+            }
+            let mut parts: Vec<&[u8]> = vec![&self.v, std::slice::from_ref(&round)];
+            parts.extend_from_slice(data);
+            self.k = hmac(&self.k, &parts);
+            self.v = hmac(&self.k, &[&self.v]);
+        }
+    }
+}
+
+impl Drbg for HmacDrbg {
+    fn name(&self) -> String {
+        "HMAC_DRBG (SHA-256)".to_string()
+    }
+
+    fn entropy_len(&self) -> usize {
+        32
+    }
+
+    fn reseed(&mut self, entropy: &[u8], additional: &[u8]) {
+        self.update(&[entropy, additional]);
+        self.reseed_counter = 1;
+    }
+
+    fn generate(&mut self, out: &mut [u8], additional: &[u8]) -> Result<(), ReseedRequired> {
+        check_request(out, self.reseed_counter)?;
+        if !additional.is_empty() {
+            self.update(&[additional]);
+        }
+        for chunk in out.chunks_mut(32) {
+            self.v = hmac(&self.k, &[&self.v]);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+        self.update(&[additional]);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    fn reseed_counter(&self) -> u64 {
+        self.reseed_counter
+    }
+}
+
+// ---------------------------------------------------------------- Hash_DRBG (10.1.1)
+
+/// seedlen for SHA-256: 440 bits
+const HASH_SEEDLEN: usize = 55;
+
+/// Hash_DRBG with SHA-256: state (V, C), both seedlen bits
+pub struct HashDrbg {
+    v: [u8; HASH_SEEDLEN],
+    c: [u8; HASH_SEEDLEN],
+    reseed_counter: u64,
+}
+
+/// Hash_df: SHA-256(counter || bits || input) for counter = 1, 2, ..., truncated
+fn hash_df(input: &[&[u8]]) -> [u8; HASH_SEEDLEN] {
+    let mut out = [0u8; HASH_SEEDLEN];
+    for (counter, chunk) in (1u8..).zip(out.chunks_mut(32)) {
+        let mut h = Sha256::new();
+        h.update([counter]);
+        h.update((8 * HASH_SEEDLEN as u32).to_be_bytes());
+        input.iter().for_each(|part| h.update(part));
+        chunk.copy_from_slice(&h.finalize()[..chunk.len()]);
+    }
+    out
+}
+
+/// v = (v + x) mod 2^(8 len), big-endian, x right-aligned
+fn add_into(v: &mut [u8], x: &[u8]) {
+    let mut carry = 0u16;
+    let mut x = x.iter().rev();
+    for byte in v.iter_mut().rev() {
+        let sum = *byte as u16 + *x.next().unwrap_or(&0) as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+}
+
+impl HashDrbg {
+    pub fn new(entropy: &[u8], nonce: &[u8], personalisation: &[u8]) -> Self {
+        let v = hash_df(&[entropy, nonce, personalisation]);
+        let c = hash_df(&[&[0x00], &v]);
+        Self { v, c, reseed_counter: 1 }
+    }
+}
+
+impl Drbg for HashDrbg {
+    fn name(&self) -> String {
+        "Hash_DRBG (SHA-256)".to_string()
+    }
+
+    fn entropy_len(&self) -> usize {
+        32
+    }
+
+    fn reseed(&mut self, entropy: &[u8], additional: &[u8]) {
+        self.v = hash_df(&[&[0x01], &self.v, entropy, additional]);
+        self.c = hash_df(&[&[0x00], &self.v]);
+        self.reseed_counter = 1;
+    }
+
+    fn generate(&mut self, out: &mut [u8], additional: &[u8]) -> Result<(), ReseedRequired> {
+        check_request(out, self.reseed_counter)?;
+        if !additional.is_empty() {
+            let w = Sha256::new().chain_update([0x02]).chain_update(self.v).chain_update(additional).finalize();
+            add_into(&mut self.v, &w);
+        }
+        // Hashgen: hash V, V + 1, V + 2, ...
+        let mut data = self.v;
+        for chunk in out.chunks_mut(32) {
+            chunk.copy_from_slice(&Sha256::digest(data)[..chunk.len()]);
+            add_into(&mut data, &[1]);
+        }
+        let h = Sha256::new().chain_update([0x03]).chain_update(self.v).finalize();
+        add_into(&mut self.v, &h);
+        let c = self.c;
+        add_into(&mut self.v, &c);
+        add_into(&mut self.v, &self.reseed_counter.to_be_bytes());
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    fn reseed_counter(&self) -> u64 {
+        self.reseed_counter
+    }
+}
+
+// ---------------------------------------------------------------- CTR_DRBG (10.2.1)
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AesKey {
+    Aes128,
+    Aes256,
+}
+
+enum Cipher {
+    Aes128(Box<Aes128>),
+    Aes256(Box<Aes256>),
+}
+
+impl Cipher {
+    fn new(key: &[u8]) -> Self {
+        match key.len() {
+            16 => Cipher::Aes128(Box::new(Aes128::new_from_slice(key).expect("16-byte key"))),
+            _ => Cipher::Aes256(Box::new(Aes256::new_from_slice(key).expect("32-byte key"))),
+        }
+    }
+
+    fn encrypt(&self, block: &[u8; 16]) -> [u8; 16] {
+        let mut b = (*block).into();
+        match self {
+            Cipher::Aes128(c) => c.encrypt_block(&mut b),
+            Cipher::Aes256(c) => c.encrypt_block(&mut b),
+        }
+        b.into()
+    }
+}
+
+/// CTR_DRBG with AES: state (Key, V), with or without the derivation function
+pub struct CtrDrbg {
+    key: Vec<u8>,
+    v: [u8; 16],
+    derivation: bool,
+    reseed_counter: u64,
+}
+
+/// Block_Cipher_df (10.3.2): CBC-MAC the input under a fixed key into a new key and
+/// block, then run that key in OFB mode for `len` bytes
+fn block_cipher_df(keylen: usize, input: &[&[u8]], len: usize) -> Vec<u8> {
+    let total: usize = input.iter().map(|p| p.len()).sum();
+    let mut s = Vec::with_capacity(total + 25);
+    s.extend_from_slice(&(total as u32).to_be_bytes());
+    s.extend_from_slice(&(len as u32).to_be_bytes());
+    input.iter().for_each(|p| s.extend_from_slice(p));
+    s.push(0x80);
+    s.resize(s.len().div_ceil(16) * 16, 0);
+
+    let fixed: Vec<u8> = (0..keylen as u8).collect();
+    let bcc_key = Cipher::new(&fixed);
+    let mut temp = Vec::with_capacity(keylen + 16);
+    for i in 0u32.. {
+        if temp.len() >= keylen + 16 {
+            break;
+        }
+        let mut iv = [0u8; 16];
+        iv[..4].copy_from_slice(&i.to_be_bytes());
+        // BCC: CBC-MAC over IV || S
+        let mut chain = bcc_key.encrypt(&iv);
+        for block in s.chunks_exact(16) {
+            let x: [u8; 16] = std::array::from_fn(|j| chain[j] ^ block[j]);
+            chain = bcc_key.encrypt(&x);
+        }
+        temp.extend_from_slice(&chain);
+    }
+    let cipher = Cipher::new(&temp[..keylen]);
+    let mut x: [u8; 16] = temp[keylen..keylen + 16].try_into().expect("16 bytes");
+    let mut out = Vec::with_capacity(len + 16);
+    while out.len() < len {
+        x = cipher.encrypt(&x);
+        out.extend_from_slice(&x);
+    }
+    out.truncate(len);
+    out
+}
+
+impl CtrDrbg {
+    /// Entropy is keylen bytes with the derivation function (plus a nonce of half that),
+    /// seedlen = keylen + 16 bytes without it (no nonce)
+    pub fn new(aes: AesKey, derivation: bool, entropy: &[u8], nonce: &[u8], personalisation: &[u8]) -> Self {
+        let keylen = if aes == AesKey::Aes128 { 16 } else { 32 };
+        let mut d = Self { key: vec![0; keylen], v: [0; 16], derivation, reseed_counter: 1 };
+        let seed = d.seed_material(&[entropy, nonce], personalisation);
+        d.update(&seed);
+        d
+    }
+
+    fn seedlen(&self) -> usize {
+        self.key.len() + 16
+    }
+
+    /// With the derivation function: df(input || extra). Without: input XOR extra,
+    /// where input is exactly seedlen bytes and extra is zero-padded to it.
+    fn seed_material(&self, input: &[&[u8]], extra: &[u8]) -> Vec<u8> {
+        if self.derivation {
+            let mut parts = input.to_vec();
+            parts.push(extra);
+            return block_cipher_df(self.key.len(), &parts, self.seedlen());
+        }
+        let mut seed = input.concat();
+        assert_eq!(seed.len(), self.seedlen(), "without the derivation function the entropy input is seedlen bytes");
+        assert!(extra.len() <= self.seedlen(), "input longer than seedlen needs the derivation function");
+        seed.iter_mut().zip(extra).for_each(|(s, e)| *s ^= e);
+        seed
+    }
+
+    fn increment(v: &mut [u8; 16]) {
+        *v = (u128::from_be_bytes(*v).wrapping_add(1)).to_be_bytes();
+    }
+
+    /// (Key, V) = leftmost seedlen bytes of E(Key, V+1) || E(Key, V+2) || ... XOR data
+    fn update(&mut self, data: &[u8]) {
+        let cipher = Cipher::new(&self.key);
+        let mut temp = Vec::with_capacity(self.seedlen() + 16);
+        while temp.len() < self.seedlen() {
+            Self::increment(&mut self.v);
+            temp.extend_from_slice(&cipher.encrypt(&self.v));
+        }
+        temp.truncate(self.seedlen());
+        temp.iter_mut().zip(data).for_each(|(t, d)| *t ^= d);
+        let keylen = self.key.len();
+        self.key.copy_from_slice(&temp[..keylen]);
+        self.v.copy_from_slice(&temp[keylen..]);
+    }
+}
+
+impl Drbg for CtrDrbg {
+    fn name(&self) -> String {
+        let df = if self.derivation { "with" } else { "no" };
+        format!("CTR_DRBG (AES-{}, {} df)", 8 * self.key.len(), df)
+    }
+
+    fn entropy_len(&self) -> usize {
+        if self.derivation { self.key.len() } else { self.seedlen() }
+    }
+
+    fn reseed(&mut self, entropy: &[u8], additional: &[u8]) {
+        let seed = self.seed_material(&[entropy], additional);
+        self.update(&seed);
+        self.reseed_counter = 1;
+    }
+
+    fn generate(&mut self, out: &mut [u8], additional: &[u8]) -> Result<(), ReseedRequired> {
+        check_request(out, self.reseed_counter)?;
+        let additional = if additional.is_empty() {
+            vec![0; self.seedlen()]
+        } else {
+            let zeros = vec![0; self.seedlen()];
+            let a = if self.derivation { self.seed_material(&[additional], &[]) } else { self.seed_material(&[&zeros], additional) };
+            self.update(&a);
+            a
+        };
+        let cipher = Cipher::new(&self.key);
+        for chunk in out.chunks_mut(16) {
+            Self::increment(&mut self.v);
+            chunk.copy_from_slice(&cipher.encrypt(&self.v)[..chunk.len()]);
+        }
+        self.update(&additional);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    fn reseed_counter(&self) -> u64 {
+        self.reseed_counter
+    }
+}
+
+// ---------------------------------------------------------------- instances
+
+/// A DRBG with its entropy source: reseeds before every request under prediction
+/// resistance, and whenever the reseed counter runs out
+pub struct Instance<D: Drbg, E: RngCore> {
+    pub drbg: D,
+    source: E,
+    prediction_resistance: bool,
+}
+
+impl<D: Drbg, E: RngCore> Instance<D, E> {
+    pub fn new(drbg: D, source: E, prediction_resistance: bool) -> Self {
+        Self { drbg, source, prediction_resistance }
+    }
+
+    pub fn reseed(&mut self, additional: &[u8]) {
+        let mut entropy = vec![0u8; self.drbg.entropy_len()];
+        self.source.fill_bytes(&mut entropy);
+        self.drbg.reseed(&entropy, additional);
+    }
+
+    /// Fill `out`, split into requests of at most `MAX_REQUEST` bytes. Under prediction
+    /// resistance the additional input goes into the reseed, as in SP 800-90A 9.3.1.
+    pub fn generate(&mut self, out: &mut [u8], additional: &[u8]) {
+        for chunk in out.chunks_mut(MAX_REQUEST) {
+            let mut additional = additional;
+            if self.prediction_resistance {
+                self.reseed(additional);
+                additional = &[];
+            }
+            if self.drbg.generate(chunk, additional) == Err(ReseedRequired) {
+                self.reseed(additional);
+                self.drbg.generate(chunk, &[]).expect("fresh after reseed");
+            }
+        }
+    }
+}
+
+impl<D: Drbg, E: RngCore> RngCore for Instance<D, E> {
+    fn next_u32(&mut self) -> u32 {
+        let mut b = [0u8; 4];
+        self.fill_bytes(&mut b);
+        u32::from_le_bytes(b)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut b = [0u8; 8];
+        self.fill_bytes(&mut b);
+        u64::from_le_bytes(b)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.generate(dest, &[]);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<D: Drbg, E: RngCore + CryptoRng> CryptoRng for Instance<D, E> {}
+
+/// An "entropy source" that replays fixed inputs, for the known-answer tests
+struct Replay(VecDeque<Vec<u8>>);
+
+impl RngCore for Replay {
+    fn next_u32(&mut self) -> u32 {
+        let mut b = [0u8; 4];
+        self.fill_bytes(&mut b);
+        u32::from_le_bytes(b)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut b = [0u8; 8];
+        self.fill_bytes(&mut b);
+        u64::from_le_bytes(b)
+    }
+
+    /// Each call takes the next recorded input whole; its length must match `dest`
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let next = self.0.pop_front().expect("test vector has no more entropy");
+        dest.copy_from_slice(&next);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------- known answers
+
+fn unhex(s: &str) -> Vec<u8> {
+    hex::decode(s.split_whitespace().collect::<String>()).expect("test vector hex")
+}
+
+/// A CAVP prediction-resistance test: instantiate, then two generate calls, each
+/// reseeding from the next entropy input with that call's additional input. The
+/// expected value is the output of the second call.
+struct PrVector {
+    entropy: &'static str,
+    nonce: &'static str,
+    personalisation: &'static str,
+    entropy_pr: [&'static str; 2],
+    additional: [&'static str; 2],
+    expected: &'static str,
+}
+
+impl PrVector {
+    fn run<D: Drbg>(&self, instantiate: impl FnOnce(&[u8], &[u8], &[u8]) -> D) -> bool {
+        let drbg = instantiate(&unhex(self.entropy), &unhex(self.nonce), &unhex(self.personalisation));
+        let source = Replay(self.entropy_pr.iter().map(|e| unhex(e)).collect());
+        let mut instance = Instance::new(drbg, source, true);
+        let expected = unhex(self.expected);
+        let mut out = vec![0u8; expected.len()];
+        for add in self.additional {
+            instance.generate(&mut out, &unhex(add));
+        }
+        out == expected
+    }
+}
+
+// From the CAVP drbgvectors_pr_true files (CAVS 14.3), as used by OpenSSL's FIPS self-tests
+
+/// HMAC_DRBG.rsp, [SHA-256], COUNT = 0
+const HMAC_PR: PrVector = PrVector {
+    entropy: "ca851911349384bffe89de1cbdc46e6831e44d34a4fb935ee285dd14b71a7488",
+    nonce: "659ba96c601dc69fc902940805ec0ca8",
+    personalisation: "e72dd8590d4ed5295515c35ed6199e9d211b8f069b3058caa6670b96ef1208d0",
+    entropy_pr: [
+        "5cacc68165a2e2ee20812f35ec73a79dbf30fd475476ac0c44fc6174cdac2b55",
+        "8df013b4d103523073917ddf6a869793059e9943fc8654549e7ab22f7c29f122",
+    ],
+    additional: [
+        "793a7ef8f6f0482beac542bb785c10f8b7b406a4de92667ab168ecc2cf7573c6",
+        "2238cdb4e23d629fe0c2a83dd8d5144ce1a6229ef41dabe2a99ff722e510b530",
+    ],
+    expected: "b1d17c002a7febd28412d8e58a7f32318e4ee3605a99b05b05d59356d5f0c6b4
+               960a4b8f963b7efa55bb6872fbeac7b99b78dea8f3531973637c946a9cab3349
+               744b24a0851dd47f2b3b460c2c61846e91181d62d42c60a4efda5ed57902bfd7
+               02b349c54952c7f644769d8ef4015ecc5f5bbd4af06134688e30050e0497fb0a",
+};
+
+/// Hash_DRBG.rsp, [SHA-256], COUNT = 14
+const HASH_PR: PrVector = PrVector {
+    entropy: "066dc8ce75b28966a685163fe2a4d427fbdb616650616ba282fc332b4e6f1220",
+    nonce: "559f7c64897083ec2d7370d9f0e5071f",
+    personalisation: "886f549aad1ac63d18cbcc6685daa2c2f79eb0894cb4aef1ac544fce57f15e11",
+    entropy_pr: [
+        "ff80b7d26a05bc8a7abe53286b0eeb733b715a205bfa4ff63703deadb6ea0ef4",
+        "c73832534681ede37e03846d3c841767297d246c689241d2e775be7ec996293d",
+    ],
+    additional: [
+        "b7215f14ac7bafd0a91772ba22f719afbd20b311636c2b1e83e4a823353fc6ea",
+        "ced31f7e0dae5bb5c043e246b29473e2fd39512ead4569eee3e3803314aba7a3",
+    ],
+    expected: "60c234cfafb468033bf195e578ce266e1465326a96a9e03f8b893670ef62754d
+               5e80d553a1f84950208b9343079f2ef856e9c570618597b5dc82a2daeaa3fd9b
+               2fd2a0d71bc62935ccb83da0679805a0e31efee4f0e513b08317faca935e3829
+               48d272db763e6df32510ff1b99fff8c60eb0dd292ebcbbc80a016ed3b00e4eab",
+};
+
+/// CTR_DRBG.rsp, [AES-128 use df], COUNT = 0
+const CTR_PR: PrVector = PrVector {
+    entropy: "92898f31fa1cff6d182f260643dff818",
+    nonce: "c2a4d972c3b9b697",
+    personalisation: "ea65ee60264e7eb60e8268c4373c5c0b",
+    entropy_pr: ["20728a06f86f8dd441e272b7c42ce810", "3db0f094f305503317863e2208f7a501"],
+    additional: ["1a40fae3cc6c7ca0f8daba59236dad1d", "9f72766cc746e5ed2e532012bc59318c"],
+    expected: "5a3539870f4d22a40924ee71c96fac720ad6f08882d0832873ec3f93d8ab4523
+               f07eac45145e939fb1d676433db6e80888f6da89087742fe1af43fc423c51f68",
+};
+
+/// Run the known-answer tests, reporting each to `check`
+fn known_answers(check: &mut dyn FnMut(&str, bool)) {
+    // HMAC_DRBG.rsp without prediction resistance or reseed, [SHA-256], COUNT = 0: two
+    // generate calls, the second one's output is compared
+    let no_pr = |entropy: &str, nonce: &str, additional: [&str; 2], expected: &str| {
+        let mut d = HmacDrbg::new(&unhex(entropy), &unhex(nonce), &[]);
+        let expected = unhex(expected);
+        let mut out = vec![0u8; expected.len()];
+        for add in additional {
+            d.generate(&mut out, &unhex(add)).expect("fresh instance");
+        }
+        out == expected
+    };
+    check(
+        "HMAC_DRBG, no reseed, COUNT 0",
+        no_pr(
+            "ca851911349384bffe89de1cbdc46e6831e44d34a4fb935ee285dd14b71a7488",
+            "659ba96c601dc69fc902940805ec0ca8",
+            ["", ""],
+            "e528e9abf2dece54d47c7e75e5fe302149f817ea9fb4bee6f4199697d04d5b89
+             d54fbb978a15b5c443c9ec21036d2460b6f73ebad0dc2aba6e624abf07745bc1
+             07694bb7547bb0995f70de25d6b29e2d3011bb19d27676c07162c8b5ccde0668
+             961df86803482cb37ed6d5c0bb8d50cf1f50d476aa0458bdaba806f48be9dcb8",
+        ),
+    );
+    check(
+        "HMAC_DRBG, no reseed, additional input, COUNT 0",
+        no_pr(
+            "d3cc4d1acf3dde0c4bd2290d262337042dc632948223d3a2eaab87da44295fbd",
+            "0109b0e729f457328aa18569a9224921",
+            [
+                "3c311848183c9a212a26f27f8c6647e40375e466a0857cc39c4e47575d53f1f6",
+                "fcb9abd19ccfbccef88c9c39bfb3dd7b1c12266c9808992e305bc3cff566e4e4",
+            ],
+            "9c7b758b212cd0fcecd5daa489821712e3cdea4467b560ef5ddc24ab47749a1f
+             1ffdbbb118f4e62fcfca3371b8fbfc5b0646b83e06bfbbab5fac30ea09ea2bc7
+             6f1ea568c9be0444b2cc90517b20ca825f2d0eccd88e7175538b85d90ab39018
+             3ca6395535d34473af6b5a5b88f5a59ee7561573337ea819da0dcc3573a22974",
+        ),
+    );
+    check("HMAC_DRBG, prediction resistance, COUNT 0", HMAC_PR.run(HmacDrbg::new));
+    check("Hash_DRBG, prediction resistance, COUNT 14", HASH_PR.run(HashDrbg::new));
+    check("CTR_DRBG AES-128 df, prediction resistance, COUNT 0", CTR_PR.run(|e, n, p| CtrDrbg::new(AesKey::Aes128, true, e, n, p)));
+
+    // AES-256 without the derivation function: instantiate, reseed, generate twice
+    // (the vector in BoringSSL's and AWS-LC's ctrdrbg_test.cc)
+    let seed = unhex("e4bc23c5089a19d86f4119cb3fa08c0a4991e0a1def17e101e4c14d9c323460a7c2fb58e0b086c6c57b55f56cae25bad");
+    let reseed = unhex("fd85a836bba85019881e8c6bad23c9061adc75477659acaea8e4a01dfe07a1832dad1c136f59d70f8653a5dc118663d6");
+    let mut d = CtrDrbg::new(AesKey::Aes256, false, &seed, &[], &[]);
+    d.reseed(&reseed, &[]);
+    let mut out = [0u8; 64];
+    for _ in 0..2 {
+        d.generate(&mut out, &[]).expect("fresh instance");
+    }
+    let want = unhex(
+        "b2cb8905c05e5950ca31895096be29ea3d5a3b82b269495554eb80fe07de43e1
+         93b9e7c3ece73b80e062b1c1f68202fbb1c52a040ea2478864295282234aaada",
+    );
+    check("CTR_DRBG AES-256 no df, reseed", out[..] == want[..]);
+
+    // All-zero seed, one maximal request, compared by digest (`CTRDRBGTest.Large` in the
+    // same ctrdrbg_test.cc)
+    let mut d = CtrDrbg::new(AesKey::Aes256, false, &[0; 48], &[], &[]);
+    let mut big = vec![0u8; MAX_REQUEST];
+    d.generate(&mut big, &[]).expect("fresh instance");
+    let want = unhex("69781596cac03f6a6ded221e26d07549a04b91583cf4e36dff41bfb9f8a81c2b");
+    check("CTR_DRBG AES-256 no df, SHA-256 of a 64 KiB request", Sha256::digest(&big)[..] == want[..]);
+}
+
+/// Known-answer tests, then the DRBGs side by side with `ChaCha20Rng`; returns whether
+/// every test passed
+pub fn run(bytes: usize) -> bool {
+    let mut all = true;
+    let mut check = |name: &str, ok: bool| {
+        all &= ok;
+        println!("  {:<58} {}", name, if ok { "ok" } else { "FAILED" });
+    };
+    println!("Known-answer tests:");
+    known_answers(&mut check);
+
+    let fresh = |n: usize| {
+        let mut b = vec![0u8; n];
+        OsRng.fill_bytes(&mut b);
+        b
+    };
+    let pers = b"w03_workingPRG_demo";
+    fn boxed<D: Drbg + 'static>(drbg: D) -> (String, Box<dyn RngCore>) {
+        (drbg.name(), Box::new(Instance::new(drbg, OsRng, false)))
+    }
+    let generators: Vec<(String, Box<dyn RngCore>)> = vec![
+        ("ChaCha20Rng".to_string(), Box::new(ChaCha20Rng::from_seed(fresh(32).try_into().expect("32 bytes")))),
+        boxed(HmacDrbg::new(&fresh(32), &fresh(16), pers)),
+        boxed(HashDrbg::new(&fresh(32), &fresh(16), pers)),
+        boxed(CtrDrbg::new(AesKey::Aes256, true, &fresh(32), &fresh(16), pers)),
+    ];
+    println!("\nThe same `prg` call on each generator, {} bytes:", bytes);
+    for (name, mut rng) in generators {
+        println!("  {:<28} {}", name, hex::encode(crate::prg(&mut rng, bytes)));
+    }
+
+    println!("\nReseed counter after three requests:");
+    for prediction_resistance in [false, true] {
+        let mut instance = Instance::new(HmacDrbg::new(&fresh(32), &fresh(16), pers), OsRng, prediction_resistance);
+        for i in 0u32..3 {
+            instance.generate(&mut [0u8; 32], &i.to_be_bytes());
+        }
+        println!(
+            "  {:<28} {} ({})",
+            if prediction_resistance { "with prediction resistance" } else { "without" },
+            instance.drbg.reseed_counter(),
+            if prediction_resistance { "reset by a reseed before every request" } else { "one seed for all three" }
+        );
+    }
+    all
+}
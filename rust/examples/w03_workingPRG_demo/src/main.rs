@@ -1,17 +1,147 @@
+mod chacha;
+mod drbg;
+mod stream;
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
 use rand::RngCore;
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 
+use stream::ChaChaStream;
+
 /// A secure PRG: expand a 256-bit seed into `len` bytes using ChaCha20Rng.
 fn prg_chacha20(seed: [u8; 32], len: usize) -> Vec<u8> {
     // Construct from a fixed-length seed (e.g., shared secret from KDF)
     let mut rng = ChaCha20Rng::from_seed(seed);
+    prg(&mut rng, len)
+}
+
+/// `len` bytes from any generator behind `RngCore` (ChaCha20Rng or a `drbg::Instance`)
+fn prg(rng: &mut impl RngCore, len: usize) -> Vec<u8> {
     let mut out = vec![0u8; len];
     rng.fill_bytes(&mut out);
     out
 }
 
+#[derive(Parser, Debug)]
+#[command(name = "w03_workingPRG_demo", about = "ChaCha20 as a PRG and as a nonce-based stream cipher")]
+struct Cli {
+    /// Without a subcommand: print a keystream from a random seed
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print a random 256-bit key in hex
+    Keygen,
+    /// Encrypt a file or stdin under a fresh nonce (written to the output header)
+    Encrypt {
+        /// Key in hex (64 digits)
+        #[arg(long, value_name = "HEX")]
+        key: String,
+        /// Nonce in hex (16 digits; default: random). Never reuse one under the same key.
+        #[arg(long, value_name = "HEX")]
+        nonce: Option<String>,
+        /// Plaintext file (default: stdin)
+        #[arg(short, long, value_name = "FILE")]
+        input: Option<PathBuf>,
+        /// Output file (default: stdout)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Decrypt a file made by `encrypt`, all of it or from an offset
+    Decrypt {
+        #[arg(long, value_name = "HEX")]
+        key: String,
+        /// File made by `encrypt` (default: stdin)
+        #[arg(short, long, value_name = "FILE")]
+        input: Option<PathBuf>,
+        /// Output file (default: stdout)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Plaintext offset to start at; the keystream seeks there directly (and so does
+        /// the input, if it is a file rather than a pipe)
+        #[arg(long, value_name = "BYTES", default_value_t = 0)]
+        offset: u64,
+        /// Bytes to decrypt from the offset (default: to the end)
+        #[arg(long, value_name = "BYTES")]
+        length: Option<u64>,
+    },
+    /// Check the from-scratch ChaCha against RFC 8439 and rand_chacha, and time it
+    Chacha {
+        /// Keystream to generate for the timing
+        #[arg(long, value_name = "MIB", default_value_t = 16)]
+        mib: usize,
+    },
+    /// Run the SP 800-90A DRBG known-answer tests and compare them with ChaCha20Rng
+    Drbg {
+        /// Output bytes to show per generator
+        #[arg(long, default_value_t = 32)]
+        bytes: usize,
+    },
+    /// Show that two messages under the same seed leak their XOR
+    Reuse {
+        #[arg(long, default_value = "Meet me at the north gate at dawn. Bring the documents.")]
+        first: String,
+        #[arg(long, default_value = "Transfer 25000 to account 7741-0093 before Friday noon.")]
+        second: String,
+    },
+}
+
 fn main() {
+    match Cli::parse().command {
+        None => demo(),
+        Some(Command::Keygen) => {
+            let mut key = [0u8; 32];
+            rand::rngs::OsRng.fill_bytes(&mut key);
+            println!("{}", hex::encode(key));
+        }
+        Some(Command::Encrypt { key, nonce, input, output }) => {
+            let key = parse_hex::<32>(&key, "--key");
+            let nonce = nonce.map_or_else(stream::random_nonce, |n| parse_hex::<8>(&n, "--nonce"));
+            let reader: Box<dyn Read> = match &input {
+                Some(path) => Box::new(BufReader::new(or_exit(File::open(path), path))),
+                None => Box::new(io::stdin().lock()),
+            };
+            let mut writer = open_output(output.as_ref());
+            or_exit(writer.write_all(stream::MAGIC).and_then(|_| writer.write_all(&nonce)), "output");
+            let n = or_exit(ChaChaStream::new(key, nonce).apply_stream(reader, writer), "stream");
+            eprintln!("Encrypted {} bytes under nonce {}", n, hex::encode(nonce));
+        }
+        Some(Command::Decrypt { key, input, output, offset, length }) => {
+            let key = parse_hex::<32>(&key, "--key");
+            let (nonce, source) = open_ciphertext(input.as_ref(), offset);
+            // Random access in the keystream: straight to the block holding the offset
+            let mut cipher = ChaChaStream::new(key, nonce);
+            cipher.seek(offset);
+            let reader: Box<dyn Read> = match length {
+                Some(len) => Box::new(source.take(len)),
+                None => source,
+            };
+            let counter = cipher.counter();
+            let n = or_exit(cipher.apply_stream(reader, open_output(output.as_ref())), "stream");
+            eprintln!("Decrypted {} bytes from offset {} (block counter {})", n, offset, counter);
+        }
+        Some(Command::Chacha { mib }) => {
+            if !chacha::run(mib) {
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Drbg { bytes }) => {
+            if !drbg::run(bytes) {
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Reuse { first, second }) => reuse(first.as_bytes(), second.as_bytes()),
+    }
+}
+
+fn demo() {
     // In practice, get the seed from a KDF or OsRng; here we demo OsRng → 32 bytes
     let mut seed = [0u8; 32];
     rand::rngs::OsRng.fill_bytes(&mut seed);
@@ -24,3 +154,95 @@ fn main() {
     println!("Seed (hex): {}", hex::encode(seed));
     println!("Keystream (hex): {}", hex::encode(keystream));
 }
+
+/// Two messages under one seed, then under one key with two nonces
+fn reuse(p1: &[u8], p2: &[u8]) {
+    let mut seed = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut seed);
+    let xor = |a: &[u8], b: &[u8]| -> Vec<u8> { a.iter().zip(b).map(|(x, y)| x ^ y).collect() };
+
+    // BAD: the same seed (and so the same keystream) for both messages
+    let c1 = xor(p1, &prg_chacha20(seed, p1.len()));
+    let c2 = xor(p2, &prg_chacha20(seed, p2.len()));
+    let leaked = xor(&c1, &c2);
+    println!("Same seed for both messages:");
+    println!("  c1 ^ c2 = {}", hex::encode(&leaked));
+    println!("  p1 ^ p2 = {}", hex::encode(xor(p1, p2)));
+    println!("  equal: {} - the keystream cancels, no key needed", leaked == xor(p1, p2));
+    // An attacker who knows (or guesses) one message reads the other
+    let recovered = xor(&leaked, p1);
+    println!("  knowing p1, (c1 ^ c2) ^ p1 = {:?}", String::from_utf8_lossy(&recovered));
+
+    // GOOD: same key, a fresh nonce per message
+    let (n1, n2) = (stream::random_nonce(), stream::random_nonce());
+    let mut c1 = p1.to_vec();
+    ChaChaStream::new(seed, n1).apply_keystream(&mut c1);
+    let mut c2 = p2.to_vec();
+    ChaChaStream::new(seed, n2).apply_keystream(&mut c2);
+    let mixed = xor(&c1, &c2);
+    println!("\nSame key, nonces {} and {}:", hex::encode(n1), hex::encode(n2));
+    println!("  c1 ^ c2 = {}", hex::encode(&mixed));
+    println!("  knowing p1, (c1 ^ c2) ^ p1 = {:?}", String::from_utf8_lossy(&xor(&mixed, p1)));
+}
+
+fn parse_hex<const N: usize>(s: &str, what: &str) -> [u8; N] {
+    match hex::decode(s.trim()).ok().and_then(|b| <[u8; N]>::try_from(b).ok()) {
+        Some(b) => b,
+        None => {
+            eprintln!("{} must be {} hex digits", what, 2 * N);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The nonce from the header of an `encrypt` file and a reader at plaintext `offset`.
+/// A regular file seeks there; stdin, pipes and FIFOs cannot, so they are read through.
+fn open_ciphertext(input: Option<&PathBuf>, offset: u64) -> ([u8; 8], Box<dyn Read>) {
+    let name = input.map_or_else(|| PathBuf::from("stdin"), PathBuf::clone);
+    let mut header = [0u8; stream::HEADER];
+    let header_read;
+    let source: Box<dyn Read> = match input.map(|path| or_exit(File::open(path), path)) {
+        Some(mut file) if (&file).stream_position().is_ok() => {
+            header_read = file.read_exact(&mut header).is_ok();
+            let Some(start) = (stream::HEADER as u64).checked_add(offset) else {
+                eprintln!("--offset {} is past the end of any file", offset);
+                std::process::exit(1);
+            };
+            or_exit(file.seek(SeekFrom::Start(start)), &name);
+            Box::new(BufReader::new(file))
+        }
+        file => {
+            let mut source: Box<dyn Read> = match file {
+                Some(file) => Box::new(BufReader::new(file)),
+                None => Box::new(io::stdin().lock()),
+            };
+            header_read = source.read_exact(&mut header).is_ok();
+            if header_read {
+                or_exit(io::copy(&mut source.by_ref().take(offset), &mut io::sink()), &name);
+            }
+            source
+        }
+    };
+    match stream::parse_header(&header).filter(|_| header_read) {
+        Some(nonce) => (nonce, source),
+        None => {
+            eprintln!("{}: not a file made by `encrypt`", name.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn open_output(path: Option<&PathBuf>) -> Box<dyn Write> {
+    match path {
+        Some(p) => Box::new(BufWriter::new(or_exit(File::create(p), p))),
+        None => Box::new(io::stdout().lock()),
+    }
+}
+
+/// Unwrap or print the error against `what` and exit
+fn or_exit<T, E: std::fmt::Display>(r: Result<T, E>, what: impl AsRef<std::path::Path>) -> T {
+    r.unwrap_or_else(|e| {
+        eprintln!("{}: {}", what.as_ref().display(), e);
+        std::process::exit(1);
+    })
+}
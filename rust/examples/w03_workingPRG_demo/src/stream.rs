@@ -0,0 +1,109 @@
+// ChaCha20 as a stream cipher: key + nonce + block counter, c = p XOR keystream.
+//
+// `prg_chacha20` stretches a seed into bytes, and that is all a stream cipher needs - but
+// the same seed always gives the same bytes, so two messages under one seed share their
+// keystream and c1 ^ c2 = p1 ^ p2 (see `reuse`). The nonce fixes that: ChaCha20's
+// input block is
+//
+//   constant x4 | key x8 | block counter x2 | nonce x2      (32-bit words)
+//
+// and `ChaCha20Rng` exposes the nonce as its stream number and the counter through its
+// word position. Every (key, nonce) pair is a separate keystream of 2^64 blocks, and
+// byte i of it is in block i / 64, so any offset can be decrypted without the ones
+// before it (`seek`).
+//
+// A random 64-bit nonce per message is safe up to about 2^32 messages per key (birthday
+// bound); a counter-based nonce is safe until it wraps.
+
+use std::io::{self, Read, Write};
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Bytes of keystream per ChaCha20 block
+pub const BLOCK: usize = 64;
+/// Magic bytes at the start of an encrypted file, followed by the 8-byte nonce
+pub const MAGIC: &[u8; 8] = b"CHACHA1\0";
+/// Bytes before the ciphertext in an encrypted file
+pub const HEADER: usize = MAGIC.len() + 8;
+
+/// Chunk size for streaming encryption
+const CHUNK: usize = 64 * 1024;
+
+pub struct ChaChaStream {
+    rng: ChaCha20Rng,
+    /// Keystream block for the current position
+    block: [u8; BLOCK],
+    /// Position in the keystream, in bytes
+    pos: u64,
+}
+
+impl ChaChaStream {
+    pub fn new(key: [u8; 32], nonce: [u8; 8]) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(key);
+        rng.set_stream(u64::from_le_bytes(nonce));
+        let mut s = Self { rng, block: [0; BLOCK], pos: 0 };
+        s.seek(0);
+        s
+    }
+
+    /// Move to byte `offset` of the keystream: block counter offset / 64, then skip the
+    /// rest. No keystream before it is computed.
+    pub fn seek(&mut self, offset: u64) {
+        let counter = offset / BLOCK as u64;
+        // whole blocks only: `fill_bytes` would drop the tail of a partly used word
+        self.rng.set_word_pos(counter as u128 * (BLOCK / 4) as u128);
+        self.rng.fill_bytes(&mut self.block);
+        self.pos = offset;
+    }
+
+    /// Block counter of the current position
+    pub fn counter(&self) -> u64 {
+        self.pos / BLOCK as u64
+    }
+
+    /// XOR the keystream into `data` (encrypts and decrypts alike)
+    pub fn apply_keystream(&mut self, data: &mut [u8]) {
+        for byte in data.iter_mut() {
+            let i = (self.pos % BLOCK as u64) as usize;
+            *byte ^= self.block[i];
+            self.pos += 1;
+            if i == BLOCK - 1 {
+                self.rng.fill_bytes(&mut self.block);
+            }
+        }
+    }
+
+    /// Encrypt or decrypt everything from `input` to `output` in fixed-size chunks;
+    /// returns the bytes processed
+    pub fn apply_stream<R: Read, W: Write>(&mut self, mut input: R, mut output: W) -> io::Result<u64> {
+        let mut buf = vec![0u8; CHUNK];
+        let mut total = 0;
+        loop {
+            let n = input.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            self.apply_keystream(&mut buf[..n]);
+            output.write_all(&buf[..n])?;
+            total += n as u64;
+        }
+        output.flush()?;
+        Ok(total)
+    }
+}
+
+/// A fresh random nonce from the OS
+pub fn random_nonce() -> [u8; 8] {
+    let mut nonce = [0u8; 8];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Nonce from the header of an encrypted file
+pub fn parse_header(header: &[u8]) -> Option<[u8; 8]> {
+    if header.len() < HEADER || &header[..MAGIC.len()] != MAGIC {
+        return None;
+    }
+    header[MAGIC.len()..HEADER].try_into().ok()
+}